@@ -0,0 +1,198 @@
+//! `Allocator` for `&Bitena`, so collections can live in the arena.
+//!
+//! The `allocator_api` feature implements the nightly `core::alloc::Allocator`,
+//! and the `allocator-api2` feature implements the stable `allocator_api2` mirror
+//! of it, as used by `hashbrown` and `allocator_api2::vec::Vec`.
+//!
+//! Deallocating only reclaims memory when it is the most recent allocation,
+//! which is also the only allocation that can grow in place.
+
+use core::alloc::Layout;
+use core::ptr::{self, NonNull};
+
+use crate::Bitena;
+
+macro_rules! impl_allocator {
+    ($(#[$meta:meta])* $allocator:path, $alloc_error:path) => {
+        $(#[$meta])*
+        unsafe impl $allocator for &Bitena<'_> {
+            #[inline]
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, $alloc_error> {
+                self.try_alloc_layout(layout)
+                    .map(|ptr| NonNull::slice_from_raw_parts(ptr, layout.size()))
+                    .map_err(|_| $alloc_error)
+            }
+
+            #[inline]
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.unbump(ptr, layout);
+            }
+
+            #[inline]
+            unsafe fn grow(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, $alloc_error> {
+                unsafe { self.try_realloc(ptr, old_layout, new_layout) }
+                    .map(|ptr| NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+                    .map_err(|_| $alloc_error)
+            }
+
+            #[inline]
+            unsafe fn grow_zeroed(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, $alloc_error> {
+                let grown = unsafe { self.grow(ptr, old_layout, new_layout)? };
+                unsafe {
+                    ptr::write_bytes(
+                        grown.cast::<u8>().as_ptr().add(old_layout.size()),
+                        0,
+                        new_layout.size() - old_layout.size(),
+                    )
+                };
+                Ok(grown)
+            }
+
+            #[inline]
+            unsafe fn shrink(
+                &self,
+                ptr: NonNull<u8>,
+                old_layout: Layout,
+                new_layout: Layout,
+            ) -> Result<NonNull<[u8]>, $alloc_error> {
+                unsafe { self.try_realloc(ptr, old_layout, new_layout) }
+                    .map(|ptr| NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+                    .map_err(|_| $alloc_error)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "allocator_api")]
+impl_allocator!(
+    /// # Example
+    ///
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let mut v = Vec::new_in(&bitena);
+    ///     v.extend([1u32, 2, 3]);
+    ///     let b = Box::new_in(String::from("Dropped normally"), &bitena);
+    ///     assert_eq!(v.len() + b.len(), 19);
+    ///     Ok(())
+    /// }
+    /// ```
+    core::alloc::Allocator,
+    core::alloc::AllocError
+);
+
+#[cfg(feature = "allocator-api2")]
+impl_allocator!(
+    /// # Example
+    ///
+    /// ```
+    /// use allocator_api2::vec::Vec;
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let mut v = Vec::new_in(&bitena);
+    ///     v.extend([1u32, 2, 3]);
+    ///     assert_eq!(v.len(), 3);
+    ///     Ok(())
+    /// }
+    /// ```
+    allocator_api2::alloc::Allocator,
+    allocator_api2::alloc::AllocError
+);
+
+#[cfg(all(test, feature = "allocator_api"))]
+mod test {
+    use crate::{Bitena, Result};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_try_vec_grows_in_place() -> Result<()> {
+        let bitena = Bitena::new(4096)?;
+        let mut v: Vec<u32, _> = Vec::with_capacity_in(4, &bitena);
+        v.extend(0..4);
+        let remaining = bitena.remaining();
+
+        v.extend(4..100);
+        assert_eq!(v, (0..100).collect::<Vec<u32>>());
+        assert_eq!(remaining - bitena.remaining(), 4 * (v.capacity() - 4), "Should grow in place");
+
+        drop(v);
+        assert_eq!(bitena.remaining(), 4096, "The last allocation is reclaimed");
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_interleaved_collections() -> Result<()> {
+        let bitena = Bitena::new(64 * 1024)?;
+        let mut a = Vec::new_in(&bitena);
+        let mut b = Vec::new_in(&bitena);
+        for i in 0..500u64 {
+            a.push(i);
+            b.push(i * 2);
+        }
+        assert_eq!(a.iter().sum::<u64>() * 2, b.iter().sum::<u64>());
+
+        let mut map = HashMap::new_in(&bitena);
+        map.insert("one", Box::new_in(1u8, &bitena));
+        map.insert("two", Box::new_in(2u8, &bitena));
+        assert_eq!(*map["two"], 2);
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "allocator-api2"))]
+mod test_api2 {
+    use crate::{Bitena, Growth, Result};
+    use allocator_api2::vec::Vec;
+    use hashbrown::HashMap;
+
+    #[test]
+    fn test_try_hashbrown() -> Result<()> {
+        let bitena = Bitena::new(64 * 1024)?;
+        let mut map = HashMap::new_in(&bitena);
+        for i in 0..100u32 {
+            map.insert(i, i * i);
+        }
+        assert_eq!(map[&9], 81);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_threads_push_concurrently() -> Result<()> {
+        let bitena = Bitena::with_growth(4096, Growth::Double, None)?;
+
+        std::thread::scope(|s| {
+            let handles: std::vec::Vec<_> = (0..4u64)
+                .map(|t| {
+                    let bitena = &bitena;
+                    s.spawn(move || {
+                        let mut v = Vec::new_in(bitena);
+                        for i in 0..1000 {
+                            v.push(t * 10_000 + i);
+                        }
+                        v.iter().sum::<u64>()
+                    })
+                })
+                .collect();
+            for (t, handle) in handles.into_iter().enumerate() {
+                let expected = (0..1000).map(|i| t as u64 * 10_000 + i).sum::<u64>();
+                assert_eq!(handle.join().unwrap(), expected);
+            }
+        });
+        Ok(())
+    }
+}
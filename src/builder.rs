@@ -0,0 +1,244 @@
+use core::alloc::Layout;
+use core::ptr;
+
+use crate::chunk::Chunk;
+use crate::{Backing, Bitena, Error, Growth, Result};
+
+/// The size, and alignment, of a transparent huge page on x86_64 and most aarch64 Linux.
+const HUGE_PAGE_SIZE: usize = 2 << 20;
+
+/// BitenaBuilder
+///
+/// Configures every aspect of a Bitena: its capacity, growth, where its memory
+/// comes from, the alignment of its buffers, huge pages, and zeroing on reset.
+/// Unlike `assert`ing constructors, `build` reports bad settings, such as a zero
+/// capacity, as an `Error`.
+///
+/// # Example
+///
+/// ```
+/// use bitena::*;
+///
+/// fn main() -> Result<()> {
+///     let bitena = BitenaBuilder::default()
+///         .capacity(64 << 20)
+///         .growth(Growth::Double)
+///         .max_capacity(1 << 30)
+///         .huge_pages(true)
+///         .zero_on_reset(true)
+///         .build()?;
+///     let packets = bitena.try_alloc_slice([0u8; 64], 1024)?;
+///     assert_eq!(packets.as_ptr() as usize % 64, 0);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitenaBuilder {
+    capacity: usize,
+    growth: Growth,
+    max_capacity: usize,
+    base_align: usize,
+    huge_pages: bool,
+    backing: Backing,
+    zero_on_reset: bool,
+}
+
+impl BitenaBuilder {
+    /// Starts a builder for an arena with the specified byte capacity, which
+    /// is otherwise configured just like `Bitena::new`.
+    pub fn new(byte_capacity: usize) -> Self {
+        Self {
+            capacity: byte_capacity,
+            growth: Growth::Fixed,
+            max_capacity: usize::MAX,
+            base_align: 1,
+            huge_pages: false,
+            backing: Backing::Heap,
+            zero_on_reset: false,
+        }
+    }
+
+    /// Sets the byte capacity of the first chunk.
+    pub fn capacity(mut self, byte_capacity: usize) -> Self {
+        self.capacity = byte_capacity;
+        self
+    }
+
+    /// Sets how the arena grows once full. See `Bitena::with_growth`.
+    pub fn growth(mut self, growth: Growth) -> Self {
+        self.growth = growth;
+        self
+    }
+
+    /// Caps the total capacity of a growable arena. It can't be below the capacity.
+    pub fn max_capacity(mut self, max_capacity: usize) -> Self {
+        self.max_capacity = max_capacity;
+        self
+    }
+
+    /// Aligns the start of every chunk to `align`, a power of two, such as 4096
+    /// for page aligned buffers. `build` returns a `Error::Layout` otherwise.
+    pub fn base_align(mut self, align: usize) -> Self {
+        self.base_align = align;
+        self
+    }
+
+    /// Aligns every chunk to 2MB, and on Linux advises the OS to back them with
+    /// transparent huge pages, which cuts down TLB misses in large, busy arenas.
+    pub fn huge_pages(mut self, huge_pages: bool) -> Self {
+        self.huge_pages = huge_pages;
+        self
+    }
+
+    /// Sets where the arena's chunks come from. See `Backing`.
+    pub fn backing(mut self, backing: Backing) -> Self {
+        self.backing = backing;
+        self
+    }
+
+    /// Zeroes every byte handed out when the arena is reset, so nothing from
+    /// one job can leak into the next, at the cost of touching the used memory.
+    /// That includes bytes given back early, by a rewind or a dropped ArenaVec,
+    /// and chunks freed by a rewind are zeroed before they are freed.
+    pub fn zero_on_reset(mut self, zero_on_reset: bool) -> Self {
+        self.zero_on_reset = zero_on_reset;
+        self
+    }
+
+    /// Creates the arena. Returns `Error::ZeroCapacity` for a zero capacity,
+    /// `Error::CapacityOverMax` for a capacity above the max capacity, and
+    /// `Error::Layout` if the base alignment isn't a power of two.
+    pub fn build<'a>(self) -> Result<Bitena<'a>> {
+        if self.capacity == 0 {
+            return Err(Error::ZeroCapacity);
+        }
+        if self.capacity > self.max_capacity {
+            return Err(Error::CapacityOverMax);
+        }
+
+        let align = if self.huge_pages {
+            self.base_align.max(HUGE_PAGE_SIZE)
+        } else {
+            self.base_align
+        };
+        let layout = Layout::from_size_align(self.capacity, align)?;
+        let head = match self.backing {
+            Backing::Heap => Chunk::new(layout, ptr::null_mut(), self.capacity)?,
+            #[cfg(target_os = "linux")]
+            Backing::Reserved => Chunk::reserve(layout, ptr::null_mut(), 0)?,
+        };
+        let mut bitena = Bitena::with_head(head, self.growth, self.max_capacity);
+        bitena.backing = self.backing;
+        bitena.zero_on_reset = self.zero_on_reset;
+        if self.huge_pages {
+            bitena.huge_pages = true;
+            bitena.head.advise_huge_pages();
+        }
+        Ok(bitena)
+    }
+}
+
+impl Default for BitenaBuilder {
+    /// A builder with no capacity set, which `build` refuses until one is.
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_base_align() -> Result<()> {
+        let bitena = Bitena::builder(100).base_align(4096).growth(Growth::Double).build()?;
+        assert_eq!(bitena.head.buf.as_ptr() as usize % 4096, 0);
+        bitena.try_alloc_slice(0u8, 100)?;
+        bitena.try_alloc(0u8)?;
+        let grown = bitena.chunk(bitena.current.load(core::sync::atomic::Ordering::Relaxed));
+        assert_eq!(grown.buf.as_ptr() as usize % 4096, 0, "Grown chunks are aligned too");
+
+        assert!(matches!(Bitena::builder(100).base_align(3).build(), Err(Error::Layout(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_huge_pages() -> Result<()> {
+        let bitena = Bitena::builder(4 << 20).huge_pages(true).max_capacity(8 << 20).build()?;
+        assert_eq!(bitena.head.buf.as_ptr() as usize % HUGE_PAGE_SIZE, 0);
+        let slice = bitena.try_alloc_slice(7u64, 1 << 19)?;
+        assert!(slice.iter().all(|&n| n == 7));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_zero_capacity() {
+        assert!(matches!(BitenaBuilder::default().build(), Err(Error::ZeroCapacity)));
+        assert!(matches!(Bitena::new(0), Err(Error::ZeroCapacity)));
+    }
+
+    #[test]
+    fn test_try_capacity_over_max() -> Result<()> {
+        assert!(matches!(Bitena::builder(100).max_capacity(64).build(), Err(Error::CapacityOverMax)));
+        assert!(matches!(Bitena::with_growth(100, Growth::Double, Some(99)), Err(Error::CapacityOverMax)));
+        let bitena = Bitena::builder(100).max_capacity(100).growth(Growth::Double).build()?;
+        assert!(matches!(bitena.try_alloc_slice(0u8, 101), Err(Error::OutOfMemory)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_zero_on_reset() -> Result<()> {
+        let mut bitena = Bitena::builder(64).zero_on_reset(true).build()?;
+        bitena.try_alloc_slice(0xAAu8, 40)?;
+        bitena.reset();
+        let zeroed = |bitena: &Bitena| unsafe {
+            core::slice::from_raw_parts(bitena.head.buf.add(24).as_ptr(), 40).iter().all(|&b| b == 0)
+        };
+        assert!(zeroed(&bitena));
+
+        // Bytes given back before the reset are zeroed too
+        {
+            let scope = bitena.scope();
+            scope.try_alloc_slice(0xBBu8, 40)?;
+        }
+        bitena.reset();
+        assert!(zeroed(&bitena));
+
+        let mut vec = crate::ArenaVec::new_in(&bitena);
+        vec.extend([0xCCu8; 40]);
+        drop(vec);
+        bitena.reset();
+        assert!(zeroed(&bitena));
+
+        // As are those given back by shrinking to a larger alignment
+        let (old, new) = (Layout::from_size_align(40, 1)?, Layout::from_size_align(8, 32)?);
+        let ptr = bitena.try_alloc_layout(old)?;
+        unsafe {
+            ptr.write_bytes(0xDD, 40);
+            bitena.try_realloc(ptr, old, new)?;
+        }
+        bitena.reset();
+        assert!(zeroed(&bitena));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_try_reserved_backing() -> Result<()> {
+        let bitena = Bitena::builder(100)
+            .backing(Backing::Reserved)
+            .base_align(1 << 16)
+            .growth(Growth::Step(1 << 20))
+            .build()?;
+        let page = crate::os::page_size();
+        assert_eq!(bitena.capacity(), (1 << 16).max(page), "Rounded up to the alignment");
+        assert_eq!(bitena.head.buf.as_ptr() as usize % (1 << 16), 0);
+
+        let big = bitena.try_alloc_slice(1u64, 1 << 16)?;
+        assert_eq!(bitena.capacity(), bitena.head.layout.size() + (1 << 20));
+        let grown = bitena.chunk(bitena.current.load(core::sync::atomic::Ordering::Relaxed));
+        assert_eq!(grown.buf.as_ptr() as usize % (1 << 16), 0, "Grown chunks are reserved too");
+        assert!(big.iter().all(|&n| n == 1));
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+#[cfg(feature = "alloc")]
+use alloc::{
+    alloc::{alloc, dealloc},
+    boxed::Box,
+};
+use core::alloc::Layout;
+use core::mem;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(target_os = "linux")]
+use crate::os;
+#[cfg(any(feature = "alloc", target_os = "linux"))]
+use crate::{Error, Result};
+
+/// How a growable Bitena sizes the next chunk once the current chunk is full.
+///
+/// A new chunk is always at least big enough for the allocation that triggered
+/// the growth, so a single large request never fails just because of the policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Growth {
+    /// Never grow. Allocations fail with `Error::OutOfMemory` when the arena is full.
+    #[default]
+    Fixed,
+    /// Each new chunk is twice the size of the current chunk.
+    Double,
+    /// Each new chunk is this many bytes.
+    Step(usize),
+}
+
+/// Where a Bitena built by a `BitenaBuilder` gets the memory for its chunks.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backing {
+    /// The global allocator, like `Bitena::new`.
+    #[default]
+    Heap,
+    /// Address space reserved from the OS, with pages committed as the arena
+    /// fills, like `Bitena::with_reserved`. Linux only.
+    #[cfg(target_os = "linux")]
+    Reserved,
+}
+
+/// A single contiguous block of arena memory.
+///
+/// Allocations bump `end_byte_idx` downwards from the end of `buf` towards zero.
+/// Grown chunks are linked to the chunk that was current before them through
+/// `prev`, which is null for the first grown chunk (the previous being the arena's head).
+pub(crate) struct Chunk {
+    pub(crate) buf: NonNull<u8>,
+    pub(crate) end_byte_idx: AtomicUsize, // Allows for interior mutability without Mutex, RefCells, Arcs
+    pub(crate) layout: Layout,            // Stores byte_capacity
+    pub(crate) prev: *mut Chunk,
+    pub(crate) total: usize, // Capacity of this chunk plus every older chunk
+    pub(crate) committed: AtomicUsize, // Bytes from here up are usable, only below 0 for reserved chunks
+    low_water: AtomicUsize, // Lowest end_byte_idx given back since the last reset, see zero_used
+    source: Source,
+}
+
+/// Where a chunk's memory came from, which decides how it is freed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Source {
+    #[cfg(feature = "alloc")]
+    Heap, // The global allocator
+    Borrowed, // Lent by the caller, never freed
+    #[cfg(target_os = "linux")]
+    Reserved, // Address space mapped from the OS, committed as the chunk fills
+}
+
+impl Chunk {
+    #[cfg(feature = "alloc")]
+    pub(crate) fn new(layout: Layout, prev: *mut Chunk, total: usize) -> Result<Self> {
+        let buf = unsafe { alloc(layout) };
+        Ok(Self {
+            buf: NonNull::new(buf).ok_or(Error::OutOfMemory)?,
+            end_byte_idx: AtomicUsize::new(layout.size()),
+            layout,
+            prev,
+            total,
+            committed: AtomicUsize::new(0),
+            low_water: AtomicUsize::new(layout.size()),
+            source: Source::Heap,
+        })
+    }
+
+    /// Reserves address space from the OS for `layout`, aligned to at least a page,
+    /// with its size rounded up to match. Pages are committed as allocations reach them.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn reserve(layout: Layout, prev: *mut Chunk, total_before: usize) -> Result<Self> {
+        let layout = layout.align_to(os::page_size())?.pad_to_align();
+        let len = layout.size();
+        Ok(Self {
+            buf: os::reserve(len, layout.align()).ok_or(Error::OutOfMemory)?,
+            end_byte_idx: AtomicUsize::new(len),
+            layout,
+            prev,
+            total: total_before + len,
+            committed: AtomicUsize::new(len),
+            low_water: AtomicUsize::new(len),
+            source: Source::Reserved,
+        })
+    }
+
+    /// Wraps `len` bytes of memory the chunk doesn't own, and so won't free.
+    ///
+    /// # Safety
+    ///
+    /// `buf` must be valid for reads and writes of `len` bytes for as long as the chunk is used.
+    pub(crate) unsafe fn from_raw_parts(buf: NonNull<u8>, len: usize) -> Self {
+        Self {
+            buf,
+            end_byte_idx: AtomicUsize::new(len),
+            layout: unsafe { Layout::from_size_align_unchecked(len, 1) },
+            prev: core::ptr::null_mut(),
+            total: len,
+            committed: AtomicUsize::new(0),
+            low_water: AtomicUsize::new(len),
+            source: Source::Borrowed,
+        }
+    }
+
+    /// Reserves `layout.size()` bytes aligned to `layout.align()`, or returns
+    /// None when this chunk does not have enough room left.
+    ///
+    /// This performs a compare and exchange loop on the AtomicUsize end_byte_idx value...
+    /// Making this algorithm safe for multi-thread apps
+    #[inline]
+    pub(crate) fn bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let base = self.buf.as_ptr() as usize;
+        loop {
+            let end_byte_idx = self.end_byte_idx.load(Ordering::Relaxed);
+            let unaligned = end_byte_idx.checked_sub(layout.size())?;
+            let aligned = (base + unaligned) & !(layout.align() - 1);
+            let new_end_byte_idx = aligned.checked_sub(base)?;
+
+            if self
+                .end_byte_idx
+                .compare_exchange_weak(
+                    end_byte_idx,     // Expected value
+                    new_end_byte_idx, // New value
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                if new_end_byte_idx < self.committed.load(Ordering::Acquire)
+                    && !self.commit(new_end_byte_idx)
+                {
+                    // Give the bytes back, unless another thread has bumped past them
+                    self.give_back(new_end_byte_idx, end_byte_idx);
+                    return None;
+                }
+                // Offsetting from buf keeps the pointer's provenance for Miri
+                return Some(unsafe { self.buf.add(new_end_byte_idx) });
+            }
+        }
+    }
+
+    /// Gives back the most recent bump, of `size` bytes at `ptr`, if nothing else
+    /// has been bumped from this chunk since. Returns whether it was given back.
+    #[inline]
+    pub(crate) fn unbump(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        let Some(idx) = self.offset_of(ptr) else {
+            return false;
+        };
+        // Any alignment padding below the previous end stays lost
+        self.give_back(idx, idx + size)
+    }
+
+    /// Moves `end_byte_idx` back up from `from` to `to`, if it is still at `from`,
+    /// remembering how low it was for `zero_used`. Returns whether it moved.
+    #[inline]
+    fn give_back(&self, from: usize, to: usize) -> bool {
+        let moved = self
+            .end_byte_idx
+            .compare_exchange(from, to, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok();
+        if moved {
+            self.low_water.fetch_min(from, Ordering::Relaxed);
+        }
+        moved
+    }
+
+    /// Resizes the most recent bump, of `old_size` bytes at `ptr`, to `layout`,
+    /// if nothing else has been bumped from this chunk since. Bumping downwards,
+    /// the block keeps its end and moves its start, so the caller must move the
+    /// contents to the returned pointer, which may overlap the old block.
+    #[inline]
+    pub(crate) fn rebump(&self, ptr: NonNull<u8>, old_size: usize, layout: Layout) -> Option<NonNull<u8>> {
+        let idx = self.offset_of(ptr)?;
+        let base = self.buf.as_ptr() as usize;
+        let unaligned = (idx + old_size).checked_sub(layout.size())?;
+        let aligned = (base + unaligned) & !(layout.align() - 1);
+        let new_end_byte_idx = aligned.checked_sub(base)?;
+
+        self.end_byte_idx
+            .compare_exchange(idx, new_end_byte_idx, Ordering::Relaxed, Ordering::Relaxed)
+            .ok()?;
+        if new_end_byte_idx > idx {
+            // Shrinking to a larger alignment gives the bytes below the new start back
+            self.low_water.fetch_min(idx, Ordering::Relaxed);
+        }
+        if new_end_byte_idx < self.committed.load(Ordering::Acquire) && !self.commit(new_end_byte_idx) {
+            self.give_back(new_end_byte_idx, idx);
+            return None;
+        }
+        Some(unsafe { self.buf.add(new_end_byte_idx) })
+    }
+
+    /// Commits the pages of a reserved chunk from `idx` up to those already committed.
+    /// Threads may commit overlapping ranges at once, which is harmless, and `committed`
+    /// only moves down once the pages below it are usable.
+    #[cold]
+    fn commit(&self, idx: usize) -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            let committed = self.committed.load(Ordering::Acquire);
+            let start = idx - idx % os::commit_step();
+            if committed <= start {
+                return true; // Another thread committed them since the caller looked
+            }
+            if !unsafe { os::commit(self.buf.add(start), committed - start) } {
+                return false;
+            }
+            self.committed.fetch_min(start, Ordering::Release);
+            true
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            // Only reserved chunks are ever uncommitted
+            let _ = idx;
+            unreachable!()
+        }
+    }
+
+    /// Returns the byte offset of `ptr` within this chunk, if it lies within it.
+    #[inline]
+    pub(crate) fn offset_of(&self, ptr: NonNull<u8>) -> Option<usize> {
+        let idx = (ptr.as_ptr() as usize).checked_sub(self.buf.as_ptr() as usize)?;
+        (idx < self.layout.size()).then_some(idx)
+    }
+
+    /// Returns the chunk to its brand new, empty state.
+    #[inline]
+    pub(crate) fn reset(&mut self) {
+        *self.end_byte_idx.get_mut() = self.layout.size();
+        *self.low_water.get_mut() = self.layout.size();
+    }
+
+    /// Moves `end_byte_idx` up to `idx` for a rewind, remembering how low it was.
+    #[inline]
+    pub(crate) fn rewind(&mut self, idx: usize) {
+        let end_byte_idx = mem::replace(self.end_byte_idx.get_mut(), idx);
+        let low_water = self.low_water.get_mut();
+        *low_water = (*low_water).min(end_byte_idx);
+    }
+
+    /// Zeroes every byte handed out since the chunk was last reset, including
+    /// those given back since. Pages never committed were never written.
+    #[inline]
+    pub(crate) fn zero_used(&mut self) {
+        let low_water = (*self.low_water.get_mut()).min(*self.end_byte_idx.get_mut());
+        let start = low_water.max(*self.committed.get_mut());
+        unsafe { self.buf.add(start).write_bytes(0, self.layout.size() - start) };
+    }
+
+    /// Advises the OS to back the chunk with transparent huge pages. Linux only.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn advise_huge_pages(&self) {
+        #[cfg(target_os = "linux")]
+        unsafe {
+            os::advise_huge_pages(self.buf, self.layout.size())
+        };
+    }
+
+    /// Lets the OS reclaim the whole pages below the top `retain` bytes, which are
+    /// the first to be reused. Memory lent by the caller is left alone, as is all
+    /// memory on platforms other than Linux. Only call this on an empty chunk.
+    /// Heap memory is discarded in place too, which assumes the global allocator got
+    /// it from private mappings. Other memory may come back holding stale bytes,
+    /// which is harmless as nothing relies on the contents of an empty chunk.
+    pub(crate) fn trim(&mut self, retain: usize) {
+        #[cfg(target_os = "linux")]
+        if self.source != Source::Borrowed {
+            let page = os::page_size();
+            let base = self.buf.as_ptr() as usize;
+            let start = base.next_multiple_of(page) - base;
+            let end = ((base + self.layout.size().saturating_sub(retain)) & !(page - 1)).saturating_sub(base);
+            if end > start {
+                unsafe { os::discard(self.buf.add(start), end - start) };
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = retain;
+    }
+}
+
+impl Drop for Chunk {
+    #[inline]
+    fn drop(&mut self) {
+        match self.source {
+            #[cfg(feature = "alloc")]
+            Source::Heap => unsafe { dealloc(self.buf.as_ptr(), self.layout) },
+            Source::Borrowed => {}
+            #[cfg(target_os = "linux")]
+            Source::Reserved => unsafe { os::release(self.buf, self.layout.size()) },
+        }
+    }
+}
+
+/// Frees every grown chunk, from `current` back to (but excluding) `until`,
+/// which is null to free all the way back to the head chunk.
+#[cfg(feature = "alloc")]
+pub(crate) fn free_grown_chunks(current: *mut Chunk, until: *mut Chunk) {
+    let mut next = current;
+    while next != until {
+        let chunk = unsafe { Box::from_raw(next) };
+        next = chunk.prev;
+    }
+}
+
+/// Without the `alloc` feature an arena never grows, so there's nothing to free.
+#[cfg(not(feature = "alloc"))]
+pub(crate) fn free_grown_chunks(_current: *mut Chunk, _until: *mut Chunk) {}
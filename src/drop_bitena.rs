@@ -0,0 +1,308 @@
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::{Bitena, Growth, Result};
+
+/// Drop glue for one allocation, stored in the arena alongside the allocation itself.
+struct DropEntry {
+    drop_fn: unsafe fn(*mut u8, usize),
+    ptr: *mut u8,
+    len: usize,
+    next: *mut DropEntry,
+}
+
+/// DropBitena
+///
+/// A Bitena that runs destructors. Every allocation of a type that needs dropping
+/// (`String`, `Vec`, `Box`, file handles, etc) records an entry in the arena,
+/// and those destructors run in reverse allocation order when the arena is
+/// reset or dropped, so owning types can live in the arena without leaking.
+///
+/// Types that don't need dropping cost exactly what they do in a plain Bitena.
+///
+/// # Example
+///
+/// ```
+/// use bitena::*;
+///
+/// fn main() -> Result<()> {
+///     let mut bitena = DropBitena::new(1024)?;
+///     let name = bitena.try_alloc(String::from("Hello"))?;
+///     name.push_str(" World");
+///     let list = bitena.try_alloc(vec![1u32, 2, 3])?;
+///     list.push(4);
+///     bitena.reset(); // The String and Vec are dropped here
+///     Ok(())
+/// }
+/// ```
+///
+/// Destructors run long after the allocation, so every element has to outlive
+/// the arena. Borrowing something that is gone before the arena is refused:
+///
+/// ```compile_fail
+/// use bitena::*;
+///
+/// struct Peek<'s>(&'s String);
+///
+/// impl Drop for Peek<'_> {
+///     fn drop(&mut self) {
+///         println!("{}", self.0);
+///     }
+/// }
+///
+/// fn main() -> Result<()> {
+///     let a = DropBitena::new(1024)?;
+///     {
+///         let s = String::from("x");
+///         a.alloc(Peek(&s));
+///     }
+///     drop(a);
+///     Ok(())
+/// }
+/// ```
+pub struct DropBitena<'a> {
+    bitena: Bitena<'a>,
+    drops: AtomicPtr<DropEntry>, // Most recent entry first
+    _invariant: PhantomData<fn(&'a ()) -> &'a ()>, // Keeps `T: 'a` from being shortened
+}
+
+impl<'a> DropBitena<'a> {
+    /// Creates a new DropBitena with the specified byte capacity.
+    ///
+    /// Drop entries are stored in the arena too, so allow a few extra bytes
+    /// for each allocation that needs dropping.
+    pub fn new(byte_capacity: usize) -> Result<Self> {
+        Ok(Self::from_bitena(Bitena::new(byte_capacity)?))
+    }
+
+    /// Creates a new growable DropBitena. See `Bitena::with_growth`.
+    pub fn with_growth(byte_capacity: usize, growth: Growth, max_capacity: Option<usize>) -> Result<Self> {
+        Ok(Self::from_bitena(Bitena::with_growth(byte_capacity, growth, max_capacity)?))
+    }
+
+    fn from_bitena(bitena: Bitena<'a>) -> Self {
+        Self {
+            bitena,
+            drops: AtomicPtr::new(ptr::null_mut()),
+            _invariant: PhantomData,
+        }
+    }
+
+    /// Allocates space for a single element and returns a mutable reference to it.
+    /// The element is dropped when the arena is reset or dropped, so it must
+    /// outlive the arena.
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: Send + 'a>(&self, val: T) -> &mut T {
+        self.try_alloc(val)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc<T: Send + 'a>(&self, val: T) -> Result<&mut T> {
+        let val = self.bitena.try_alloc_leaking(val)?;
+        if mem::needs_drop::<T>() {
+            unsafe { self.push_drop::<T>(val, 1)? };
+        }
+        Ok(val)
+    }
+
+    /// Allocates a slice of `len` clones of `initial_value`. Each element is
+    /// dropped when the arena is reset or dropped, so they must outlive the arena.
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice<T: Clone + Send + 'a>(&self, initial_value: T, len: usize) -> &mut [T] {
+        self.try_alloc_slice(initial_value, len)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_slice<T: Clone + Send + 'a>(&self, initial_value: T, len: usize) -> Result<&mut [T]> {
+        let ptr = self.bitena.try_alloc_layout(Layout::array::<T>(len)?)?.cast::<T>();
+        let slice = unsafe {
+            if len > 0 {
+                for i in 0..len - 1 {
+                    ptr.add(i).write(initial_value.clone());
+                }
+                ptr.add(len - 1).write(initial_value);
+            }
+            core::slice::from_raw_parts_mut(ptr.as_ptr(), len)
+        };
+        if mem::needs_drop::<T>() && len > 0 {
+            unsafe { self.push_drop::<T>(slice.as_mut_ptr(), len)? };
+        }
+        Ok(slice)
+    }
+
+    /// Allocates space for a str and returns a read-only reference, &str.
+    #[inline]
+    pub fn alloc_str(&self, st: &str) -> &str {
+        self.bitena.alloc_str(st)
+    }
+
+    #[inline]
+    pub fn try_alloc_str(&self, st: &str) -> Result<&str> {
+        self.bitena.try_alloc_str(st)
+    }
+
+    /// Returns the number of bytes remaining in the arena's current chunk.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.bitena.remaining()
+    }
+
+    /// Returns the total number of bytes in all of the arena's chunks.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.bitena.capacity()
+    }
+
+    /// Drops every element in reverse allocation order, then resets the arena.
+    pub fn reset(&mut self) {
+        self.run_drops();
+        self.bitena.reset();
+    }
+
+    /// Records drop glue for `len` elements of T at `ptr`. If there's no room
+    /// left for the entry, the elements are dropped right away and the error returned.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to `len` initialized elements allocated from this arena.
+    unsafe fn push_drop<T>(&self, ptr: *mut T, len: usize) -> Result<()> {
+        unsafe fn drop_slice<T>(ptr: *mut u8, len: usize) {
+            unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(ptr as *mut T, len)) };
+        }
+
+        let entry = match self.bitena.try_alloc(DropEntry {
+            drop_fn: drop_slice::<T>,
+            ptr: ptr as *mut u8,
+            len,
+            next: ptr::null_mut(),
+        }) {
+            Ok(entry) => entry,
+            Err(e) => {
+                unsafe { drop_slice::<T>(ptr as *mut u8, len) };
+                return Err(e);
+            }
+        };
+
+        let mut head = self.drops.load(Ordering::Relaxed);
+        loop {
+            entry.next = head;
+            match self.drops.compare_exchange_weak(
+                head,                    // Expected value
+                entry as *mut DropEntry, // New value
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    fn run_drops(&mut self) {
+        let mut next = mem::replace(self.drops.get_mut(), ptr::null_mut());
+        while !next.is_null() {
+            let entry = unsafe { &*next };
+            unsafe { (entry.drop_fn)(entry.ptr, entry.len) };
+            next = entry.next;
+        }
+    }
+}
+
+impl Drop for DropBitena<'_> {
+    fn drop(&mut self) {
+        self.run_drops();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone)]
+    struct Noisy(u32, Arc<Mutex<Vec<u32>>>);
+
+    impl Drop for Noisy {
+        fn drop(&mut self) {
+            self.1.lock().unwrap().push(self.0);
+        }
+    }
+
+    #[test]
+    fn test_try_drops_in_reverse_order() -> Result<()> {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let mut bitena = DropBitena::new(1024)?;
+
+        bitena.try_alloc(Noisy(1, dropped.clone()))?;
+        bitena.try_alloc(42u64)?;
+        bitena.try_alloc(Noisy(2, dropped.clone()))?;
+        assert!(dropped.lock().unwrap().is_empty());
+
+        bitena.reset();
+        assert_eq!(*dropped.lock().unwrap(), vec![2, 1]);
+        assert_eq!(bitena.remaining(), 1024);
+
+        bitena.try_alloc(Noisy(3, dropped.clone()))?;
+        drop(bitena);
+        assert_eq!(*dropped.lock().unwrap(), vec![2, 1, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_owning_types() -> Result<()> {
+        let bitena = DropBitena::with_growth(64, Growth::Double, None)?;
+
+        let s = bitena.try_alloc(String::from("Hello"))?;
+        s.push_str(" World");
+        let v = bitena.try_alloc(vec![1u32, 2, 3])?;
+        v.extend(4..100);
+        let boxes = bitena.try_alloc_slice(Box::new(7u32), 10)?;
+        *boxes[3] = 8;
+
+        assert_eq!(s, "Hello World");
+        assert_eq!(v.len(), 99);
+        assert_eq!(boxes.iter().map(|b| **b).sum::<u32>(), 71);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_slice_drops_each_clone() -> Result<()> {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let bitena = DropBitena::new(1024)?;
+
+        bitena.try_alloc_slice(Noisy(5, dropped.clone()), 3)?;
+        bitena.try_alloc_slice(Noisy(6, dropped.clone()), 0)?;
+        assert_eq!(*dropped.lock().unwrap(), vec![6]);
+
+        drop(bitena);
+        assert_eq!(*dropped.lock().unwrap(), vec![6, 5, 5, 5]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_zero_sized_drops() -> Result<()> {
+        use std::sync::atomic::AtomicUsize;
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Marker;
+        impl Drop for Marker {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let mut bitena = DropBitena::new(1024)?;
+        bitena.try_alloc(Marker)?;
+        bitena.try_alloc(Marker)?;
+        bitena.reset();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+        Ok(())
+    }
+}
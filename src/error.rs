@@ -1,13 +1,16 @@
-use std::alloc::LayoutError;
-use std::fmt;
+use core::alloc::LayoutError;
+use core::fmt;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
     OutOfMemory,
-    PointerUnderflow,
-    Layout(std::alloc::LayoutError),
+    InvalidCheckpoint,
+    SymbolOverflow,
+    ZeroCapacity,
+    CapacityOverMax,
+    Layout(core::alloc::LayoutError),
 }
 
 impl fmt::Display for Error {
@@ -15,13 +18,16 @@ impl fmt::Display for Error {
         match self {
             Error::OutOfMemory => write!(f, "Out of Memory"),
             Error::Layout(e) => write!(f, "Layout Error: {}", e),
-            Error::PointerUnderflow => write!(f, "Pointer underflow"),
+            Error::InvalidCheckpoint => write!(f, "Invalid checkpoint"),
+            Error::SymbolOverflow => write!(f, "Too many symbols"),
+            Error::ZeroCapacity => write!(f, "Capacity must be greater than zero"),
+            Error::CapacityOverMax => write!(f, "Capacity must not exceed the max capacity"),
         }
     }
 }
 
-impl std::error::Error for Error {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
         match self {
             Error::Layout(e) => Some(e),
             _ => None,
@@ -34,3 +40,35 @@ impl From<LayoutError> for Error {
         Error::Layout(error)
     }
 }
+
+/// The error from `try_alloc_try_with`: either the arena couldn't allocate,
+/// or the initializer closure failed.
+#[derive(Debug)]
+pub enum AllocOrInitError<E> {
+    Alloc(Error),
+    Init(E),
+}
+
+impl<E: fmt::Display> fmt::Display for AllocOrInitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllocOrInitError::Alloc(e) => write!(f, "{}", e),
+            AllocOrInitError::Init(e) => write!(f, "Initialization Error: {}", e),
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for AllocOrInitError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            AllocOrInitError::Alloc(e) => Some(e),
+            AllocOrInitError::Init(e) => Some(e),
+        }
+    }
+}
+
+impl<E> From<Error> for AllocOrInitError<E> {
+    fn from(error: Error) -> Self {
+        AllocOrInitError::Alloc(error)
+    }
+}
@@ -0,0 +1,374 @@
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr::NonNull;
+use core::sync::atomic::Ordering;
+
+use crate::{Bitena, Result};
+
+/// A compact, `Copy`, lifetime-free reference to a T allocated with `Bitena::alloc_handle`.
+///
+/// It holds the T's byte offset into the arena, so structures can store handles
+/// without borrowing the arena. Offsets continue from one chunk into the next,
+/// so handles work with growable arenas too.
+pub struct Handle<T> {
+    offset: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    #[inline]
+    fn new(offset: usize) -> Self {
+        Self {
+            offset,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the byte offset of the item in its arena.
+    #[inline]
+    pub fn offset(self) -> usize {
+        self.offset
+    }
+}
+
+// Derives would needlessly require T to implement each trait
+impl<T> Clone for Handle<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.offset.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({})", self.offset)
+    }
+}
+
+/// A `Handle` that also records the arena generation it was allocated in.
+///
+/// Generations change on every reset or rewind and are never reused by any arena,
+/// so `get_gen` can tell a live handle from a stale or foreign one and return None,
+/// instead of reading whatever now occupies its offset.
+///
+/// On targets without 64 bit atomics there are only `usize::MAX` generations.
+/// Once they run out, `get_gen` returns None for every handle.
+pub struct GenHandle<T> {
+    handle: Handle<T>,
+    generation: u64,
+}
+
+impl<T> GenHandle<T> {
+    /// Returns the byte offset of the item in its arena.
+    #[inline]
+    pub fn offset(self) -> usize {
+        self.handle.offset
+    }
+
+    /// Returns the generation of the arena the item was allocated in.
+    #[inline]
+    pub fn generation(self) -> u64 {
+        self.generation
+    }
+}
+
+impl<T> Clone for GenHandle<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GenHandle<T> {}
+
+impl<T> PartialEq for GenHandle<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for GenHandle<T> {}
+
+impl<T> Hash for GenHandle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.handle.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for GenHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GenHandle({}, generation {})", self.handle.offset, self.generation)
+    }
+}
+
+/// Generations are handed out from one counter, so no two arenas ever share one.
+#[cfg(target_has_atomic = "64")]
+static GENERATIONS: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+
+#[cfg(target_has_atomic = "64")]
+#[inline]
+pub(crate) fn next_generation() -> u64 {
+    GENERATIONS.fetch_add(1, Ordering::Relaxed)
+}
+
+// Embedded targets without 64 bit atomics count in usize instead. That count
+// could wrap and reuse a generation, so it stops at the last one instead.
+#[cfg(not(target_has_atomic = "64"))]
+static GENERATIONS: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+
+#[cfg(not(target_has_atomic = "64"))]
+#[inline]
+pub(crate) fn next_generation() -> u64 {
+    GENERATIONS
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |g| g.checked_add(1))
+        .map_or(EXHAUSTED, |g| g as u64)
+}
+
+/// The generation of every arena once the generations have run out, which no
+/// `GenHandle` is ever valid for.
+const EXHAUSTED: u64 = u64::MAX;
+
+impl Bitena<'_> {
+    /// Allocates an element and returns a `Handle` to it instead of a reference.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// struct Node {
+    ///     value: u32,
+    ///     next: Option<Handle<Node>>,
+    /// }
+    ///
+    /// fn main() -> Result<()> {
+    ///     let mut bitena = Bitena::new(1024)?;
+    ///     let tail = bitena.try_alloc_handle(Node { value: 2, next: None })?;
+    ///     let head = bitena.try_alloc_handle(Node { value: 1, next: Some(tail) })?;
+    ///
+    ///     unsafe {
+    ///         bitena.get_mut(tail).value = 3;
+    ///         let next = bitena.get(head).next.unwrap();
+    ///         assert_eq!(bitena.get(next).value, 3);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    pub fn alloc_handle<T>(&self, val: T) -> Handle<T> {
+        self.try_alloc_handle(val)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    pub fn try_alloc_handle<T>(&self, val: T) -> Result<Handle<T>> {
+        let ptr = NonNull::from(self.try_alloc(val)?).cast::<u8>();
+        if mem::size_of::<T>() == 0 {
+            return Ok(Handle::new(0));
+        }
+        Ok(Handle::new(
+            self.offset_of(ptr).expect("Allocations are always in one of the chunks"),
+        ))
+    }
+
+    /// Returns a reference to the item behind a handle.
+    ///
+    /// # Safety
+    ///
+    /// The handle must have come from `alloc_handle` on this arena, and the arena
+    /// must not have been reset or rewound past it since. See `GenHandle` for handles
+    /// that check this.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle's offset lies outside the arena.
+    #[inline]
+    pub unsafe fn get<T>(&self, handle: Handle<T>) -> &T {
+        unsafe { self.handle_ptr(handle).as_ref() }
+    }
+
+    /// Returns a mutable reference to the item behind a handle.
+    ///
+    /// # Safety
+    ///
+    /// The same as for `get`.
+    #[inline]
+    pub unsafe fn get_mut<T>(&mut self, handle: Handle<T>) -> &mut T {
+        unsafe { self.handle_ptr(handle).as_mut() }
+    }
+
+    /// Allocates an element and returns a `GenHandle` to it, which, unlike a `Handle`,
+    /// is checked against the arena's generation on every access.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let mut bitena = Bitena::new(1024)?;
+    ///     let handle = bitena.try_alloc_gen_handle(42u32)?;
+    ///     *bitena.get_gen_mut(handle).unwrap() += 1;
+    ///     assert_eq!(bitena.get_gen(handle), Some(&43));
+    ///
+    ///     bitena.reset();
+    ///     bitena.try_alloc(7u32)?;
+    ///     assert_eq!(bitena.get_gen(handle), None, "Stale after reset");
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    pub fn alloc_gen_handle<T: Send + Sync>(&self, val: T) -> GenHandle<T> {
+        self.try_alloc_gen_handle(val)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    pub fn try_alloc_gen_handle<T: Send + Sync>(&self, val: T) -> Result<GenHandle<T>> {
+        Ok(GenHandle {
+            handle: self.try_alloc_handle(val)?,
+            generation: self.generation,
+        })
+    }
+
+    /// Returns a reference to the item behind a `GenHandle`, or None if the handle
+    /// is from an earlier generation or another arena.
+    #[inline]
+    pub fn get_gen<T: Send + Sync>(&self, handle: GenHandle<T>) -> Option<&T> {
+        // Same generation means same arena, with nothing released since the allocation
+        self.is_live(handle).then(|| unsafe { self.get(handle.handle) })
+    }
+
+    /// Returns a mutable reference to the item behind a `GenHandle`, or None if the
+    /// handle is from an earlier generation or another arena.
+    #[inline]
+    pub fn get_gen_mut<T: Send + Sync>(&mut self, handle: GenHandle<T>) -> Option<&mut T> {
+        self.is_live(handle).then(|| unsafe { self.get_mut(handle.handle) })
+    }
+
+    #[inline]
+    fn is_live<T>(&self, handle: GenHandle<T>) -> bool {
+        handle.generation == self.generation && self.generation != EXHAUSTED
+    }
+
+    #[inline]
+    fn handle_ptr<T>(&self, handle: Handle<T>) -> NonNull<T> {
+        if mem::size_of::<T>() == 0 {
+            return NonNull::dangling();
+        }
+        self.ptr_at(handle.offset)
+            .unwrap_or_else(|| panic!("Invalid handle: {:?}", handle))
+            .cast()
+    }
+
+    /// Returns the offset of `ptr` counted across all chunks, oldest first.
+    pub(crate) fn offset_of(&self, ptr: NonNull<u8>) -> Option<usize> {
+        let mut current = self.current.load(Ordering::Acquire);
+        loop {
+            let chunk = self.chunk(current);
+            if let Some(idx) = chunk.offset_of(ptr) {
+                return Some(chunk.total - chunk.layout.size() + idx);
+            }
+            if current.is_null() {
+                return None;
+            }
+            current = chunk.prev;
+        }
+    }
+
+    /// Returns the pointer at `offset` counted across all chunks, oldest first.
+    pub(crate) fn ptr_at(&self, offset: usize) -> Option<NonNull<u8>> {
+        if offset < self.head.layout.size() {
+            return Some(unsafe { self.head.buf.add(offset) });
+        }
+        let mut current = self.current.load(Ordering::Acquire);
+        while !current.is_null() {
+            let chunk = self.chunk(current);
+            let base = chunk.total - chunk.layout.size();
+            if (base..chunk.total).contains(&offset) {
+                return Some(unsafe { chunk.buf.add(offset - base) });
+            }
+            current = chunk.prev;
+        }
+        None
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use crate::{Bitena, Growth, Handle, Result};
+
+    #[test]
+    fn test_try_handles_across_chunks() -> Result<()> {
+        let mut bitena = Bitena::with_growth(64, Growth::Double, None)?;
+        let handles: Vec<Handle<u64>> = (0..100)
+            .map(|i| bitena.try_alloc_handle(i as u64))
+            .collect::<Result<_>>()?;
+        assert!(bitena.capacity() > 64);
+        assert!(handles.windows(2).all(|w| w[0].offset() != w[1].offset()));
+
+        for (i, handle) in handles.iter().enumerate() {
+            assert_eq!(unsafe { *bitena.get(*handle) }, i as u64);
+        }
+        unsafe { *bitena.get_mut(handles[99]) += 1 };
+        assert_eq!(unsafe { *bitena.get(handles[99]) }, 100);
+
+        let marker = bitena.try_alloc_handle(())?;
+        assert_eq!(unsafe { bitena.get(marker) }, &());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid handle")]
+    fn test_invalid_handle_panic() {
+        let mut bitena = Bitena::with_growth(8, Growth::Double, None).unwrap();
+        bitena.alloc(0u64);
+        let handle = bitena.alloc_handle(1u64);
+        bitena.reset();
+        unsafe { bitena.get(handle) };
+    }
+
+    #[test]
+    fn test_try_gen_handles_go_stale() -> Result<()> {
+        let mut bitena = Bitena::with_growth(16, Growth::Double, None)?;
+        let kept = bitena.try_alloc_gen_handle(1u64)?;
+        let checkpoint = bitena.checkpoint();
+        let grown = bitena.try_alloc_gen_handle([2u64; 4])?;
+        assert_eq!(bitena.get_gen(grown), Some(&[2; 4]));
+        assert_eq!(kept.generation(), grown.generation());
+
+        bitena.rewind(checkpoint)?;
+        assert_eq!(bitena.get_gen(grown), None);
+        assert_eq!(bitena.get_gen(kept), None, "Rewind starts a new generation");
+        let fresh = bitena.try_alloc_gen_handle(3u64)?;
+        assert_eq!(bitena.get_gen_mut(fresh), Some(&mut 3));
+
+        let other = Bitena::new(16)?;
+        let foreign = other.try_alloc_gen_handle(4u64)?;
+        assert_ne!(other.generation(), bitena.generation());
+        assert_eq!(bitena.get_gen(foreign), None);
+        assert_eq!(other.get_gen(foreign), Some(&4));
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::{PoisonError, RwLock};
+
+use crate::{Bitena, Error, Result};
+
+/// A compact id for a string stored in an `Interner`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    #[inline]
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// Interner
+///
+/// A thread-safe string interner that stores each distinct string in a Bitena
+/// exactly once. Interning hands out a `Symbol`, which resolves back to a `&str`
+/// living as long as the arena.
+///
+/// Looking up a string already interned only takes a read lock, so many threads
+/// can do so at once.
+///
+/// # Example
+///
+/// ```
+/// use bitena::*;
+///
+/// fn main() -> Result<()> {
+///     let bitena = Bitena::new(1024)?;
+///     let interner = Interner::new(&bitena);
+///     let a = interner.try_intern("ident")?;
+///     let b = interner.try_intern(&String::from("ident"))?;
+///     assert_eq!(a, b);
+///     assert_eq!(interner.resolve(a), Some("ident"));
+///     assert_eq!(bitena.remaining(), 1024 - 5);
+///     Ok(())
+/// }
+/// ```
+pub struct Interner<'a> {
+    bitena: &'a Bitena<'a>,
+    inner: RwLock<InternerInner<'a>>,
+}
+
+#[derive(Default)]
+struct InternerInner<'a> {
+    symbols: HashMap<&'a str, Symbol>,
+    strs: Vec<&'a str>, // Indexed by Symbol
+}
+
+impl<'a> Interner<'a> {
+    /// Creates an empty interner storing its strings in `bitena`.
+    pub fn new(bitena: &'a Bitena<'a>) -> Self {
+        Self {
+            bitena,
+            inner: RwLock::new(InternerInner::default()),
+        }
+    }
+
+    /// Returns the Symbol for `st`, storing it in the arena if it is new.
+    #[inline]
+    pub fn intern(&self, st: &str) -> Symbol {
+        self.try_intern(st)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    pub fn try_intern(&self, st: &str) -> Result<Symbol> {
+        if let Some(symbol) = self.get(st) {
+            return Ok(symbol);
+        }
+
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        // Another thread may have interned it while we waited for the lock
+        if let Some(&symbol) = inner.symbols.get(st) {
+            return Ok(symbol);
+        }
+        let symbol = Symbol(u32::try_from(inner.strs.len()).map_err(|_| Error::SymbolOverflow)?);
+        let st = self.bitena.try_alloc_str(st)?;
+        inner.symbols.insert(st, symbol);
+        inner.strs.push(st);
+        Ok(symbol)
+    }
+
+    /// Returns the Symbol for `st` if it has already been interned.
+    #[inline]
+    pub fn get(&self, st: &str) -> Option<Symbol> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        inner.symbols.get(st).copied()
+    }
+
+    /// Returns the string for `symbol`, or None if no string was interned as it.
+    /// Symbols aren't tied to their interner, so one from another interner may
+    /// resolve to an unrelated string.
+    #[inline]
+    pub fn resolve(&self, symbol: Symbol) -> Option<&'a str> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        inner.strs.get(symbol.0 as usize).copied()
+    }
+
+    /// Returns the number of distinct strings interned.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).strs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_dedupes() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+        let interner = Interner::new(&bitena);
+        assert!(interner.is_empty());
+
+        let foo = interner.try_intern("foo")?;
+        let bar = interner.try_intern("bar")?;
+        let empty = interner.try_intern("")?;
+        assert_eq!(interner.try_intern("foo")?, foo);
+        assert_ne!(foo, bar);
+        assert_eq!(interner.len(), 3);
+        assert_eq!(bitena.remaining(), 1024 - 6);
+
+        assert_eq!(interner.resolve(bar), Some("bar"));
+        assert_eq!(interner.resolve(empty), Some(""));
+        assert_eq!(interner.get("bar"), Some(bar));
+        assert_eq!(interner.get("baz"), None);
+        assert_eq!(interner.resolve(Symbol(99)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_threads() -> Result<()> {
+        let bitena = Bitena::new(64 * 1024)?;
+        let interner = Interner::new(&bitena);
+        let names: Vec<String> = (0..200).map(|i| format!("name_{}", i % 50)).collect();
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for name in &names {
+                        let symbol = interner.intern(name);
+                        assert_eq!(interner.resolve(symbol), Some(name.as_str()));
+                    }
+                });
+            }
+        });
+        assert_eq!(interner.len(), 50);
+        Ok(())
+    }
+}
@@ -27,13 +27,13 @@
 //! bitena = "0.1"
 //! ```
 //!
-//! ```rust
-//!   use bitena::Bitena;
+#![cfg_attr(feature = "alloc", doc = "```rust")]
+#![cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+//!   use bitena::{Bitena, alloc_fmt};
 //!   fn main() {
-//!     let mut bitena = Bitena::new(1024).expect("Failed to allocate memory");
+//!     let bitena = Bitena::new(1024).expect("Failed to allocate memory");
 //!     let num = bitena.alloc(42u32);
-//!     let stnum = format!("Num: {}", *num);
-//!     let s = bitena.alloc_str(&stnum);
+//!     let s = alloc_fmt!(bitena, "Num: {}", *num);
 //!     println!("{}  {:?}", *num, s);
 //!   }
 //! ```
@@ -43,33 +43,130 @@
 //! ## new(byte_capacity)
 //! Allocate a new Arena with a specified capacity.
 //!
+//! ## with_growth(byte_capacity, growth, max_capacity)
+//! Allocate a new Arena that chains on more chunks, per the `Growth` policy,
+//! instead of returning `Error::OutOfMemory` when it fills up.
+//!
+//! ## builder(byte_capacity)
+//! Configure an Arena with a BitenaBuilder: growth, max capacity, heap or reserved
+//! backing, page or other base alignment, transparent huge pages on Linux, and
+//! zeroing on reset. Bad settings, like a zero capacity, are reported as Errors.
+//!
+//! ## from_buffer(&mut buf) or from_raw_parts(ptr, len)
+//! Create a fixed size Arena over memory you provide: a stack array, a static
+//! buffer, an mmap'd region, etc. The arena never frees it.
+//!
+//! ## with_reserved(virtual_size) (Linux)
+//! Reserve address space for a huge Arena without using any memory up front.
+//! Pages are committed as allocations reach them.
+//!
 //! ## alloc(item) or try_alloc(item)
 //! Allocate an element or structure in the Arena
 //!
+//! ## alloc_leaking(item) or try_alloc_leaking(item)
+//! Allocate an element that needs dropping, knowingly leaking whatever it owns
+//!
+//! ## alloc_with(|| item), try_alloc_with(|| item) or try_alloc_try_with(|| Ok(item))
+//! Reserve space first, then build the element in place, for large structs
+//!
 //! ## alloc_slice(init_value, items) or try_alloc_slice(init_value, items)
 //! Allocate a slice vector of elements
 //!
+//! ## alloc_slice_copy, alloc_slice_clone or alloc_slice_fill_with(items, |i| ...)
+//! Allocate a slice of copies, of clones, or of values built from each index.
+//! alloc_slice is the same as alloc_slice_copy. All have try_ versions.
+//!
+//! ## `alloc_slice_from_slice(&[items])` or `alloc_slice_from_iter(iter)`
+//! Allocate a slice copied from an existing slice, or collected from an
+//! ExactSizeIterator. Both have try_ versions.
+//!
+//! ## alloc_uninit(), alloc_uninit_slice(items) or alloc_zeroed_slice(items)
+//! Allocate uninitialized, or zeroed, space without supplying an initial value.
+//! All have try_ versions.
+//!
+//! ## alloc_layout(layout) or try_alloc_layout(layout)
+//! Allocate raw memory of any size and alignment, for building your own containers
+//!
+//! ## try_realloc(ptr, old_layout, new_layout)
+//! Resize a raw allocation, in place when it is the arena's most recent one
+//!
+//! ## alloc_handle(item), get(handle) or get_mut(handle)
+//! Allocate an element and get back a compact, Copy, lifetime-free Handle,
+//! holding its offset in the arena, instead of a reference.
+//!
+//! ## alloc_gen_handle(item), get_gen(handle) or get_gen_mut(handle)
+//! Like alloc_handle, but the GenHandle records the arena's generation, which
+//! changes on every reset or rewind, so get_gen returns None for stale handles
+//! instead of whatever now lives at their offset.
+//!
 //! ## alloc_str(&str) or try_alloc_str(&str)
 //! Store a &str in the Arena
 //!
+//! ## checkpoint(), rewind(checkpoint) or scope()
+//! Save the arena's position and later release only what was allocated after it.
+//! The guard returned by scope() rewinds automatically when dropped.
+//!
+//! ## ArenaRelPtr
+//! A pointer stored as an offset from its own address, so linked structures in the
+//! arena stay valid when the memory holding them is copied, saved, or mapped elsewhere.
+//!
+//! ## ArenaVec::new_in(&bitena)
+//! A growable vector in the arena, with push, extend, insert and truncate. It grows
+//! in place while it is the most recent allocation, and into_slice() or
+//! into_bump_slice() turn it into a plain arena slice.
+//!
+//! ## ArenaString::new_in(&bitena) or alloc_fmt!(bitena, "...", args)
+//! A growable string in the arena that implements fmt::Write, and a format!
+//! style macro that formats straight into the arena.
+//!
+//! ## Interner::new(&bitena)
+//! A thread-safe string interner. Each distinct string is stored in the arena
+//! once, and interning returns a compact Symbol that resolves back to the &str.
+//!
+//! ## TypedBitena
+//! An arena for a single type that can iterate over, count, or move out its items
+//! in allocation order, and drops them when it is reset or dropped.
+//!
+//! ## DropBitena
+//! A Bitena that runs the destructors of its `String`s, `Vec`s, `Box`es and other
+//! owning types, in reverse allocation order, when it is reset or dropped.
+//!
+//! ## Allocator for &Bitena (nightly, `allocator_api` feature)
+//! Use the arena for std collections: `Vec::new_in(&bitena)`, `Box::new_in(x, &bitena)`.
+//! Freeing or growing the most recent allocation happens in place.
+//!
+//! On stable Rust, the `allocator-api2` feature does the same for `hashbrown`,
+//! `allocator_api2::vec::Vec` and other collections built on `allocator_api2`.
+//!
+//! ## no_std
+//! Bitena is `no_std` with `default-features = false`. The `alloc` feature lets
+//! arenas allocate from the global allocator with `new` and `with_growth`, and adds
+//! DropBitena and TypedBitena. Without it, arenas are built with `from_buffer`. The
+//! default `std` feature adds the Interner.
+//!
 //! ## reset()
 //! Reset the arena. This requires that all allocations are vacated, and
 //! re-initializes the Arena to it's brand new state.
 //!
+//! ## reset_and_trim(retain)
+//! Reset the arena, and give the memory beyond the first `retain` bytes back to
+//! the OS, so a long running service doesn't keep its peak memory use.
+//!
 //! ## Tradeoffs
 //!
 //!   - Individual Items are not resizeable. Each element or item allocated from
-//!     the arena is a fixed size. You need to individually Box<T> any items, 
-//!     (Strings, Vecs, Fat Pointers, file handles, etc) to avoid leaking memory.
+//!     the arena is a fixed size.
 //!
 //!   - The entire arena will be dropped in a single operation. Individual Drop
-//!     operations will not be performed on the Arena's contents. This then will
-//!     leak any memory separately allocated as with Strings and Vecs.
+//!     operations will not be performed on the Arena's contents, so Bitena refuses,
+//!     at compile time, types that need dropping (Strings, Vecs, Boxes, file handles,
+//!     etc). Use a `DropBitena` for those, or `alloc_leaking` to leak them on purpose.
 //!
 //!   - **No item Reclamation**: Any unused allocations are stuck until
 //!     the whole arena is dropped or reset().
 //!
-//!   - **Fixed Size**: The arena has a set fixed size that doesn't grow.
+//!   - **Fixed Size**: The arena has a set fixed size that doesn't grow, unless
+//!     created with `with_growth`. Even then, items never move between chunks.
 //!
 //! # MIRI to the Rescue
 //!
@@ -107,7 +204,7 @@
 //!  - Scoped Allocator, so you can restore memory in stages - See bump-scope
 //!  - Memory Pools - See shared-arena
 //!  - Boxed Allocations or Collections so you CAN use an arena with strings
-//!       and vecs. See Rodeo and Bumpalo
+//!    and vecs. See Rodeo and Bumpalo
 //!  - Memory Layout Control, Rewinding, Thread-Local memory lakes, etc (See lake)
 //!  - Detect Use after free - See arena-allocator
 //!
@@ -117,36 +214,37 @@
 //!
 //! ❌ - Don't do this:
 //! ```ignore
-//!      let v = arena.try_alloc("Hello".to_string())?;    <== Still allocates from the heap
+//!      let v = bitena.try_alloc("Hello".to_string())?;    <== Compile error, the String would leak
 //! ```
 //!
 //! ✅ - Do this instead:
 //! ```ignore
-//!      let v = bitena.try_alloc("Hello")?;   <==  Arena based READ ONLY str
+//!      let v = bitena.try_alloc_str("Hello")?;   <==  Arena based READ ONLY str
 //! ```
 //!
-//! ✅ - Do this instead: allocate a Box in the Arena, the string data from the heap.
+//! ✅ - Do this instead: use a DropBitena, which drops the String on reset() or drop.
 //! ```ignore
-//!      let v = bitena.try_alloc(Box("Hello".to_string()))?; <== St from heap, Box handles drop
+//!      let v = drop_bitena.try_alloc("Hello".to_string())?; <== Str from heap, arena handles drop
 //! ```
 //!
 //!
-//! ❌ - Don't do this:  
+//! ❌ - Don't do this:
 //! ```ignore
-//!      let v = bitena.try_alloc(vec![42u32; 10])?;  <== Allocates data on the heap
+//!      let v = bitena.try_alloc(vec![42u32; 10])?;  <== Compile error, the Vec would leak
 //! ```
 //!
 //! ✅ - Do this instead:
 //! ```ignore
-//!      let v = bitena.try_alloc_slice(42u32, 10)?;   <==  Returns a 10 element MUTABLE slice
+//!      let v = bitena.try_alloc_slice(42u32, 10)?;   <==  Returns a 10 element MUTABLE fixed size slice
 //! ```
 //!
-//! ✅ - Do this instead, allocate a Box in the Arena, Box allocates/deallocates Vec from the heap.
+//! ✅ - Do this instead: use a DropBitena, which drops the Vec on reset() or drop.
 //! ```ignore
-//!      let v = bitena.try_alloc(Box(vec![42u32; 10]))?;  <==  Vec on heap, Box handles drop
+//!      let v = drop_bitena.try_alloc(vec![42u32; 10])?;  <==  Vec on heap, arena handles drop
 //! ```
-//! In both cases of the Don't do this, a fat pointer will be stored in the arena,
-//! and memory for the data or string will be allocated and LEAKED on the heap. In 
+//! In both cases of the Don't do this, a fat pointer would be stored in the arena,
+//! and memory for the data or string allocated and LEAKED on the heap.
+//!
 //!
 //! ## License
 //! MIT
@@ -163,15 +261,59 @@
 //! Reverse allocations inspired by:
 //!   https://fitzgen.com/2019/11/01/always-bump-downwards.html
 
-use std::alloc::{Layout, dealloc};
-use std::marker::PhantomData;
-use std::mem;
-use std::num::NonZero;
-use std::ptr::{copy_nonoverlapping, NonNull};
-use std::sync::atomic::{AtomicUsize, Ordering};
-
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+#![allow(clippy::needless_doctest_main)] // The examples show complete programs
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ptr::{self, copy_nonoverlapping, NonNull};
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+#[cfg(any(feature = "allocator_api", feature = "allocator-api2"))]
+mod allocator;
+#[cfg(feature = "alloc")]
+mod builder;
+mod chunk;
+#[cfg(feature = "alloc")]
+mod drop_bitena;
 mod error;
-pub use self::error::{Error, Result};
+mod handle;
+#[cfg(target_os = "linux")]
+mod os;
+#[cfg(feature = "std")]
+mod interner;
+mod rel_ptr;
+mod scope;
+mod string;
+#[cfg(feature = "alloc")]
+mod typed;
+mod vec;
+mod zeroable;
+use self::chunk::{Chunk, free_grown_chunks};
+use self::handle::next_generation;
+#[cfg(feature = "alloc")]
+pub use self::builder::BitenaBuilder;
+#[cfg(feature = "alloc")]
+pub use self::chunk::Backing;
+pub use self::chunk::Growth;
+#[cfg(feature = "alloc")]
+pub use self::drop_bitena::DropBitena;
+pub use self::error::{AllocOrInitError, Error, Result};
+pub use self::handle::{GenHandle, Handle};
+#[cfg(feature = "std")]
+pub use self::interner::{Interner, Symbol};
+pub use self::rel_ptr::ArenaRelPtr;
+pub use self::scope::{BitenaScope, Checkpoint};
+pub use self::string::ArenaString;
+#[cfg(feature = "alloc")]
+pub use self::typed::TypedBitena;
+pub use self::vec::ArenaVec;
+pub use self::zeroable::Zeroable;
 
 
 /// Bitena
@@ -185,16 +327,18 @@ pub use self::error::{Error, Result};
 ///
 ///  - ALLOCATED ITEMS DON'T DROP
 ///
-///  - FIXED SIZE ARENA
+///  - FIXED SIZE ARENA, unless created with a `Growth` policy
 ///
 /// any element types that reserver memory or resources, file handles,
-/// vecs, and strings will leak memory if allocated on Bitena because 
+/// vecs, and strings would leak memory if allocated on Bitena because
 /// the arena drops in one operation, without dropping each individual
-/// item as appropriate.
+/// item as appropriate. So they fail to compile, unless allocated with
+/// `alloc_leaking`. See `DropBitena` for an arena that does drop them.
 ///
 /// # Example
 ///
-/// ```
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
 /// use bitena::*;
 ///
 /// fn main() -> Result<()> {
@@ -208,14 +352,25 @@ pub use self::error::{Error, Result};
 /// }
 /// ```
 pub struct Bitena<'a> {
-    buf: NonNull<u8>,
-    end_byte_idx: AtomicUsize, // Allows for interior mutability without Mutex, RefCells, Arcs
-    layout: Layout,            // Stores byte_capacity
+    head: Chunk,
+    current: AtomicPtr<Chunk>, // Newest grown chunk, or null while still in head
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))] // Only used to grow
+    growth: Growth,
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    max_capacity: usize,
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    huge_pages: bool, // Grown chunks are advised to use huge pages too
+    #[cfg(feature = "alloc")]
+    backing: Backing, // For grown chunks
+    zero_on_reset: bool,
+    generation: u64, // Changes on every reset or rewind, see GenHandle
+    resets: u64,     // Changes on every reset only, see Checkpoint
     _marker: PhantomData<&'a ()>,
 }
 
 impl<'a> Bitena<'a> {
     /// Creates a new Arena with the specified byte capacity.
+    /// Returns `Error::ZeroCapacity` if it is zero.
     ///
     /// # Example
     ///
@@ -228,23 +383,129 @@ impl<'a> Bitena<'a> {
     ///     Ok(())
     /// }
     /// ```
+    #[cfg(feature = "alloc")]
     pub fn new(byte_capacity: usize) -> Result<Self> {
-        assert!(byte_capacity > 0, "Capacity must be greater than zero.");
+        Self::with_growth(byte_capacity, Growth::Fixed, None)
+    }
 
-        let layout = Layout::from_size_align(byte_capacity, mem::align_of::<u8>())?;
-        let buf = unsafe {
-            let ptr = std::alloc::alloc(layout);
-            if ptr.is_null() {
-                return Err(Error::OutOfMemory);
-            }
-            ptr as *mut u8
-        };
-        Ok(Self {
-            buf: NonNull::new(buf).ok_or(Error::PointerUnderflow)?,
-            end_byte_idx: AtomicUsize::new(byte_capacity),
-            layout,
+    /// Creates a new growable Arena. Once the first `byte_capacity` bytes are
+    /// used up, additional chunks are chained on according to `growth`, until
+    /// the total capacity of all chunks would exceed `max_capacity`, which returns
+    /// `Error::CapacityOverMax` if it is below `byte_capacity`.
+    ///
+    /// References handed out from earlier chunks stay valid until the arena is
+    /// reset or dropped. Allocations never span two chunks, so the bytes left
+    /// at the end of a full chunk are not reused.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::with_growth(1024, Growth::Double, Some(8192))?;
+    ///     let first = bitena.try_alloc_slice(1u8, 1000)?;
+    ///     let second = bitena.try_alloc_slice(2u8, 1000)?;
+    ///     assert_eq!(bitena.capacity(), 3072);
+    ///     assert_eq!(first[0] + second[0], 3);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn with_growth(byte_capacity: usize, growth: Growth, max_capacity: Option<usize>) -> Result<Self> {
+        BitenaBuilder::new(byte_capacity)
+            .growth(growth)
+            .max_capacity(max_capacity.unwrap_or(usize::MAX))
+            .build()
+    }
+
+    /// Starts a `BitenaBuilder`, for arenas with aligned or huge page backed buffers.
+    #[cfg(feature = "alloc")]
+    pub fn builder(byte_capacity: usize) -> BitenaBuilder {
+        BitenaBuilder::new(byte_capacity)
+    }
+
+    /// Creates a fixed size Arena over memory owned by the caller, such as a stack
+    /// array or a static buffer. The memory is never freed by the arena, and the
+    /// borrow keeps the buffer alive and untouched for as long as the arena is.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bitena::*;
+    /// use std::mem::MaybeUninit;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let mut buf = [MaybeUninit::uninit(); 256];
+    ///     let bitena = Bitena::from_buffer(&mut buf);
+    ///     let slice = bitena.try_alloc_slice(7u64, 16)?;
+    ///     assert_eq!(slice.iter().sum::<u64>(), 112);
+    ///     assert!(bitena.try_alloc_slice(0u64, 32).is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_buffer(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        let len = buf.len();
+        unsafe { Self::from_raw_parts(NonNull::from(buf).cast(), len) }
+    }
+
+    /// Creates a fixed size Arena over `len` bytes at `ptr`, such as an mmap'd region
+    /// or memory from another allocator. The memory is never freed by the arena.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure:
+    /// - `ptr` is valid for reads and writes of `len` bytes
+    /// - Nothing else accesses the memory for the lifetime `'a` of the arena
+    pub unsafe fn from_raw_parts(ptr: NonNull<u8>, len: usize) -> Self {
+        let head = unsafe { Chunk::from_raw_parts(ptr, len) };
+        Self::with_head(head, Growth::Fixed, len)
+    }
+
+    /// Creates a fixed size Arena over `virtual_size` bytes, rounded up to whole
+    /// pages, of reserved address space. Memory is only committed as allocations
+    /// reach it, so an arena can reserve tens of GBs and only pay for what it touches.
+    ///
+    /// Linux only. Returns `Error::OutOfMemory` if the address space can't be
+    /// reserved, or later, when an allocation's pages can't be committed.
+    /// See `BitenaBuilder` for growable or aligned reserved arenas.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::with_reserved(16 << 30)?; // 16GB
+    ///     let slice = bitena.try_alloc_slice(1u8, 1 << 20)?;
+    ///     assert_eq!(slice.len(), 1 << 20);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn with_reserved(virtual_size: usize) -> Result<Self> {
+        if virtual_size == 0 {
+            return Err(Error::ZeroCapacity);
+        }
+        let head = Chunk::reserve(Layout::from_size_align(virtual_size, 1)?, ptr::null_mut(), 0)?;
+        let size = head.layout.size();
+        Ok(Self::with_head(head, Growth::Fixed, size))
+    }
+
+    fn with_head(head: Chunk, growth: Growth, max_capacity: usize) -> Self {
+        Self {
+            head,
+            current: AtomicPtr::new(ptr::null_mut()),
+            growth,
+            max_capacity,
+            huge_pages: false,
+            #[cfg(feature = "alloc")]
+            backing: Backing::Heap,
+            zero_on_reset: false,
+            generation: next_generation(),
+            resets: 0,
             _marker: PhantomData,
-        })
+        }
     }
 
     /// Allocates space for a single element and returns a mutable reference to it.
@@ -257,7 +518,8 @@ impl<'a> Bitena<'a> {
     ///
     /// # Example
     ///
-    /// ```
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
     /// use bitena::*;
     ///
     /// fn main() -> Result<()> {
@@ -269,135 +531,591 @@ impl<'a> Bitena<'a> {
     ///     Ok(())
     /// }
     /// ```
+    ///
+    /// Types that need dropping are refused, since the arena would leak them:
+    ///
+    /// ```compile_fail
+    /// use bitena::*;
+    ///
+    /// let bitena = Bitena::new(1024).unwrap();
+    /// let leaked = bitena.alloc(vec![1u32, 2, 3]);
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, val: T) -> &mut T {
+        self.try_alloc(val)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc<T>(&self, val: T) -> Result<&mut T> {
+        assert_no_drop::<T>();
+        self.try_alloc_leaking(val)
+    }
+
+    /// Allocates space for a single element, like `alloc`, but also accepts types
+    /// that need dropping. Their destructors never run, so any memory or resources
+    /// they own are leaked, unless dropped by hand with `std::ptr::drop_in_place`.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let name = bitena.try_alloc_leaking(String::from("Leaked"))?;
+    ///     assert_eq!(name, "Leaked");
+    ///     unsafe { std::ptr::drop_in_place(name) };
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_leaking<T>(&self, val: T) -> &mut T {
+        self.try_alloc_leaking(val)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_leaking<T>(&self, val: T) -> Result<&mut T> {
+        let ptr = self.try_alloc_layout(Layout::new::<T>())?.cast::<T>();
+        unsafe {
+            ptr.write(val);
+            Ok(&mut *ptr.as_ptr())
+        }
+    }
+
+    /// Allocates space for a single element, then calls `f` to build it in place.
+    ///
+    /// The slot is reserved before `f` runs, so the optimizer can write `f`'s result
+    /// straight into the arena instead of moving it through the stack, which matters
+    /// for large structs and buffers.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1 << 20)?;
+    ///     let buf = bitena.try_alloc_with(|| [0u8; 512 * 1024])?;
+    ///     buf[0] = 1;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_with<T, F: FnOnce() -> T>(&self, f: F) -> &mut T {
+        self.try_alloc_with(f)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_with<T, F: FnOnce() -> T>(&self, f: F) -> Result<&mut T> {
+        assert_no_drop::<T>();
+
+        let ptr = self.try_alloc_layout(Layout::new::<T>())?.cast::<T>();
+        unsafe {
+            ptr.write(f());
+            Ok(&mut *ptr.as_ptr())
+        }
+    }
+
+    /// Allocates space for a single element, then calls the fallible `f` to build it.
+    ///
+    /// If `f` fails, its error is returned and the reservation is rolled back,
+    /// as long as nothing else has been allocated from the arena in the meantime.
+    /// Note the `Result` itself passes through the stack.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let parsed = bitena.try_alloc_try_with(|| "42".parse::<u32>());
+    ///     assert_eq!(*parsed.unwrap(), 42);
+    ///
+    ///     let failed = bitena.try_alloc_try_with(|| "forty two".parse::<u32>());
+    ///     assert!(matches!(failed, Err(AllocOrInitError::Init(_))));
+    ///     assert_eq!(bitena.remaining(), 1020);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_try_with<T, E, F>(&self, f: F) -> core::result::Result<&mut T, E>
+    where
+        F: FnOnce() -> core::result::Result<T, E>,
+    {
+        self.try_alloc_try_with(f).map_err(|e| match e {
+            AllocOrInitError::Alloc(e) => panic!("Bitena Failed: {}", e),
+            AllocOrInitError::Init(e) => e,
+        })
+    }
+
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_try_with<T, E, F>(&self, f: F) -> core::result::Result<&mut T, AllocOrInitError<E>>
+    where
+        F: FnOnce() -> core::result::Result<T, E>,
+    {
+        assert_no_drop::<T>();
+
+        let layout = Layout::new::<T>();
+        let ptr = self.try_alloc_layout(layout)?;
+        match f() {
+            Ok(val) => unsafe {
+                let ptr = ptr.cast::<T>();
+                ptr.write(val);
+                Ok(&mut *ptr.as_ptr())
+            },
+            Err(e) => {
+                self.unbump(ptr, layout);
+                Err(AllocOrInitError::Init(e))
+            }
+        }
+    }
+
+    /// Allocates space for a slice and returns a mutable slice reference.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure:
+    /// - The arena has enough remaining memory
+    /// - The reference is not used after the arena is reset or dropped
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///         let slice = bitena.try_alloc_slice(0u32, 4)?;
+    ///         for i in 0..4 {
+    ///             slice[i] = i as u32;
+    ///         }
+    ///         println!("{:?}", slice);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice<T: Copy>(&self, initial_value: T, len: usize) -> &mut [T] {
+        self.alloc_slice_copy(initial_value, len)
+    }
+
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_slice<T: Copy>(&self, initial_value: T, len: usize) -> Result<&mut [T]> {
+        self.try_alloc_slice_copy(initial_value, len)
+    }
+
+    /// Allocates a slice of `len` copies of `initial_value`. Slices of bytes are
+    /// filled with a single memset.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let slice = bitena.try_alloc_slice_copy(7u16, 4)?;
+    ///     assert_eq!(slice, [7, 7, 7, 7]);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, initial_value: T, len: usize) -> &mut [T] {
+        self.try_alloc_slice_copy(initial_value, len)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_slice_copy<T: Copy>(&self, initial_value: T, len: usize) -> Result<&mut [T]> {
+        let sizet = mem::size_of::<T>();
+
+        let ptr = self.try_alloc_layout(Layout::array::<T>(len)?)?.as_ptr();
+        unsafe {
+            // Initialize New Slice
+            if sizet == 1 {
+                // Bytes are VERY FAST to initialize
+                let byte_ptr = &initial_value as *const T as *const u8;
+                ptr::write_bytes(ptr, *byte_ptr, len * sizet);
+            } else {
+                // Not so fast!!!
+                let initial_value_ptr = &initial_value as *const T as *const u8;
+                for i in 0..len {
+                    copy_nonoverlapping(initial_value_ptr, ptr.add(i * sizet), sizet);
+                }
+            }
+            Ok(core::slice::from_raw_parts_mut(ptr as *mut T, len))
+        }
+    }
+
+    /// Allocates a slice of `len` clones of `initial_value`.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Token(&'static str);
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let slice = bitena.try_alloc_slice_clone(Token("none"), 3)?;
+    ///     slice[1] = Token("some");
+    ///     assert_eq!(slice[0], Token("none"));
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_clone<T: Clone>(&self, initial_value: T, len: usize) -> &mut [T] {
+        self.try_alloc_slice_clone(initial_value, len)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_slice_clone<T: Clone>(&self, initial_value: T, len: usize) -> Result<&mut [T]> {
+        self.try_alloc_slice_fill_with(len, |_| initial_value.clone())
+    }
+
+    /// Allocates a slice of `len` elements, building each one from its index with `f`.
+    ///
+    /// If `f` panics, the elements written so far are never handed out.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let squares = bitena.try_alloc_slice_fill_with(5, |i| (i * i) as u32)?;
+    ///     assert_eq!(squares, [0, 1, 4, 9, 16]);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_fill_with<T, F>(&self, len: usize, f: F) -> &mut [T]
+    where
+        F: FnMut(usize) -> T,
+    {
+        self.try_alloc_slice_fill_with(len, f)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_slice_fill_with<T, F>(&self, len: usize, mut f: F) -> Result<&mut [T]>
+    where
+        F: FnMut(usize) -> T,
+    {
+        assert_no_drop::<T>();
+
+        let ptr = self.try_alloc_layout(Layout::array::<T>(len)?)?.cast::<T>();
+        unsafe {
+            for i in 0..len {
+                ptr.add(i).write(f(i));
+            }
+            Ok(core::slice::from_raw_parts_mut(ptr.as_ptr(), len))
+        }
+    }
+
+    /// Allocates a copy of `src` and returns it as a mutable slice.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let v = vec![1u32, 2, 3];
+    ///     let slice = bitena.try_alloc_slice_from_slice(&v)?;
+    ///     slice[0] = 10;
+    ///     assert_eq!(slice, [10, 2, 3]);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_from_slice<T: Copy>(&self, src: &[T]) -> &mut [T] {
+        self.try_alloc_slice_from_slice(src)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_slice_from_slice<T: Copy>(&self, src: &[T]) -> Result<&mut [T]> {
+        let ptr = self.try_alloc_layout(Layout::for_value(src))?.cast::<T>();
+        unsafe {
+            copy_nonoverlapping(src.as_ptr(), ptr.as_ptr(), src.len());
+            Ok(core::slice::from_raw_parts_mut(ptr.as_ptr(), src.len()))
+        }
+    }
+
+    /// Allocates a slice holding the items of an iterator whose length is known up front.
+    ///
+    /// The whole slice is reserved at once, and the items are written straight into
+    /// the arena. Should the iterator yield fewer items than it reported, the slice
+    /// is shortened to the items actually written. If the iterator panics, nothing
+    /// is handed out.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let evens = bitena.try_alloc_slice_from_iter((0..5u32).map(|i| i * 2))?;
+    ///     assert_eq!(evens, [0, 2, 4, 6, 8]);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_from_iter<T, I>(&self, iter: I) -> &mut [T]
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.try_alloc_slice_from_iter(iter)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_slice_from_iter<T, I>(&self, iter: I) -> Result<&mut [T]>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        assert_no_drop::<T>();
+
+        let mut iter = iter.into_iter();
+        let len = iter.len();
+        let ptr = self.try_alloc_layout(Layout::array::<T>(len)?)?.cast::<T>();
+        let mut written = 0;
+        unsafe {
+            while written < len {
+                match iter.next() {
+                    Some(item) => ptr.add(written).write(item),
+                    None => break,
+                }
+                written += 1;
+            }
+            Ok(core::slice::from_raw_parts_mut(ptr.as_ptr(), written))
+        }
+    }
+
+    /// Allocates uninitialized space for a single element.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let slot = bitena.try_alloc_uninit::<u64>()?;
+    ///     let num = slot.write(42);
+    ///     assert_eq!(*num, 42);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_uninit<T>(&self) -> &mut MaybeUninit<T> {
+        self.try_alloc_uninit()
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_uninit<T>(&self) -> Result<&mut MaybeUninit<T>> {
+        let ptr = self.try_alloc_layout(Layout::new::<T>())?.cast::<MaybeUninit<T>>();
+        Ok(unsafe { &mut *ptr.as_ptr() })
+    }
+
+    /// Allocates uninitialized space for a slice, skipping the cost of filling it,
+    /// for example to read data directly into arena memory.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let buf = bitena.try_alloc_uninit_slice::<u8>(5)?;
+    ///     for (slot, byte) in buf.iter_mut().zip(b"Hello") {
+    ///         slot.write(*byte);
+    ///     }
+    ///     // Every element is now initialized
+    ///     let hello = unsafe { &*(buf as *const [_] as *const [u8]) };
+    ///     assert_eq!(hello, b"Hello");
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_uninit_slice<T>(&self, len: usize) -> &mut [MaybeUninit<T>] {
+        self.try_alloc_uninit_slice(len)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_uninit_slice<T>(&self, len: usize) -> Result<&mut [MaybeUninit<T>]> {
+        let ptr = self.try_alloc_layout(Layout::array::<T>(len)?)?.cast::<MaybeUninit<T>>();
+        Ok(unsafe { core::slice::from_raw_parts_mut(ptr.as_ptr(), len) })
+    }
+
+    /// Allocates a slice with every byte set to zero, for types where that is
+    /// a valid value.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let counts = bitena.try_alloc_zeroed_slice::<u32>(16)?;
+    ///     counts[3] += 1;
+    ///     assert_eq!(counts.iter().sum::<u32>(), 1);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_zeroed_slice<T: Zeroable>(&self, len: usize) -> &mut [T] {
+        self.try_alloc_zeroed_slice(len)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc_zeroed_slice<T: Zeroable>(&self, len: usize) -> Result<&mut [T]> {
+        assert_no_drop::<T>();
+
+        let ptr = self.try_alloc_layout(Layout::array::<T>(len)?)?;
+        unsafe {
+            ptr::write_bytes(ptr.as_ptr(), 0, len * mem::size_of::<T>());
+            Ok(core::slice::from_raw_parts_mut(ptr.cast::<T>().as_ptr(), len))
+        }
+    }
+
+    /// Allocates raw memory for an arbitrary `Layout`, honouring any size and
+    /// alignment, such as 64 byte SIMD buffers or page aligned blocks. Every
+    /// other allocation method is built on this one.
+    ///
+    /// The memory is uninitialized. Note that the padding needed to reach a large
+    /// alignment comes out of the arena's capacity too. A zero sized layout
+    /// returns a dangling, but well aligned, pointer without using any capacity.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    /// use std::alloc::Layout;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let bitena = Bitena::new(1024)?;
+    ///     let layout = Layout::from_size_align(256, 64).unwrap();
+    ///     let ptr = bitena.try_alloc_layout(layout)?;
+    ///     assert_eq!(ptr.as_ptr() as usize % 64, 0);
+    ///     Ok(())
+    /// }
+    /// ```
     #[inline]
-    pub fn alloc<T>(&self, val: T) -> &mut T {
-        self.try_alloc(val)
+    pub fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        self.try_alloc_layout(layout)
             .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
     }
 
-    pub fn try_alloc<T>(&self, val: T) -> Result<&mut T> {
-        let sizet = std::mem::size_of::<T>();
-        let align = std::mem::align_of::<T>();
-        debug_assert!(sizet > 0, "Can't alloc 0 bytes");
-        debug_assert!(align.is_power_of_two(), "Alignment must be a power of two");
-
-        unsafe {
-            loop {
-                let end_byte_idx = self.end_byte_idx.load(Ordering::Relaxed);
-                let ptr_num = (self.buf.as_ptr().add(end_byte_idx as usize) as usize)
-                    .checked_sub(sizet)
-                    .ok_or(Error::PointerUnderflow)?;
-
-                //let ptr = (ptr as usize & !(align - 1)) as *mut u8;  // Align Ptr pre-Miri
-                let ptr = self
-                    .buf
-                    .with_addr(NonZero::new(ptr_num & !(align - 1)).ok_or(Error::PointerUnderflow)?)
-                    .as_ptr() as *mut u8;
-
-                if (ptr as usize) < self.buf.as_ptr() as usize {
-                    return Err(Error::OutOfMemory);
-                }
-                let new_end_byte_idx =
-                    (ptr as usize).saturating_sub(self.buf.as_ptr() as usize) as usize;
-
-                if let Ok(_) = self.end_byte_idx.compare_exchange_weak(
-                    end_byte_idx,     // Expected value
-                    new_end_byte_idx, // New value
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
-                ) {
-                    std::ptr::write(ptr as *mut T, val);
-                    return Ok(&mut *(ptr as *mut T));
-                }
+    /// Reserves memory for `layout` from the current chunk, growing the arena
+    /// when the current chunk is full and a Growth policy allows it.
+    #[inline]
+    pub fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>> {
+        if layout.size() == 0 {
+            // Zero sized types and empty slices need no memory, just a well aligned pointer
+            return Ok(unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) });
+        }
+        loop {
+            let current = self.current.load(Ordering::Acquire);
+            if let Some(ptr) = self.chunk(current).bump(layout) {
+                return Ok(ptr);
             }
+            self.grow(current, layout)?;
         }
     }
 
-    /// Allocates space for a slice and returns a mutable slice reference.
+    /// Resizes an allocation from `old` to `new`, returning where the contents now live.
+    ///
+    /// Shrinking never moves. The most recent allocation grows in place, everything
+    /// else is copied to a fresh allocation, leaving the old one unused. The first
+    /// `min(old.size(), new.size())` bytes are preserved.
     ///
     /// # Safety
     ///
-    /// The caller must ensure:
-    /// - The arena has enough remaining memory
-    /// - The reference is not used after the arena is reset or dropped
+    /// `ptr` must be an allocation of `old` from this arena, with no outstanding
+    /// references to it, since the contents may move.
     ///
     /// # Example
     ///
-    /// ```
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
     /// use bitena::*;
+    /// use std::alloc::Layout;
     ///
     /// fn main() -> Result<()> {
     ///     let bitena = Bitena::new(1024)?;
-    ///         let slice = bitena.try_alloc_slice(0u32, 4)?;
-    ///         for i in 0..4 {
-    ///             slice[i] = i as u32;
-    ///         }
-    ///         println!("{:?}", slice);
+    ///     let old = Layout::array::<u32>(4).unwrap();
+    ///     let new = Layout::array::<u32>(8).unwrap();
+    ///     let ptr = bitena.try_alloc_layout(old)?;
+    ///     let ptr = unsafe { bitena.try_realloc(ptr, old, new)? };
+    ///     assert_eq!(bitena.remaining(), 1024 - 32);
     ///     Ok(())
     /// }
     /// ```
-    #[inline]
-    pub fn alloc_slice<T>(&self, initial_value: T, len: usize) -> &mut [T] {
-        self.try_alloc_slice(initial_value, len)
-            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
-    }
-
-    pub fn try_alloc_slice<T>(&self, initial_value: T, len: usize) -> Result<&mut [T]> {
-        let sizet = std::mem::size_of::<T>();
-        let align = std::mem::align_of::<T>();
-        debug_assert!(sizet > 0, "Can't alloc 0 bytes");
-        debug_assert!(align.is_power_of_two(), "Alignment must be a power of two");
-
-        // This performs a compare and exchange loop on atomicUsize for the end_byte_idx value...
-        // Making this algorithm safe for multi-thread apps
-        unsafe {
-            loop {
-                let end_byte_idx = self.end_byte_idx.load(Ordering::Relaxed);
-                let ptr_num = (self.buf.as_ptr().add(end_byte_idx) as usize)
-                    .checked_sub(len * sizet)
-                    .ok_or(Error::PointerUnderflow)?;
-
-                //let ptr = (ptr as usize & !(align - 1)) as *mut u8;  // Align Ptr pre-Miri
-                let ptr = self
-                    .buf
-                    .with_addr(NonZero::new(ptr_num & !(align - 1)).ok_or(Error::PointerUnderflow)?)
-                    .as_ptr() as *mut u8;
-
-                if (ptr as *mut u8 as usize) < self.buf.as_ptr() as usize {
-                    return Err(Error::OutOfMemory);
-                }
-                let new_end_byte_idx =
-                    (ptr as usize).saturating_sub(self.buf.as_ptr() as usize) as usize;
-
-                if let Ok(_) = self.end_byte_idx.compare_exchange_weak(
-                    end_byte_idx,     // Expected value
-                    new_end_byte_idx, // New value
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
-                ) {
-                    // Initialize New Slice
-                    if sizet == 1 {
-                        // Bytes are VERY FAST to initialize
-                        let byte_ptr = &initial_value as *const T as *const u8;
-                        std::ptr::write_bytes(ptr, *byte_ptr, len * sizet);
-                    } else if is_all_zeros(&initial_value) {
-                        // Zeroed Memory is too
-                        std::ptr::write_bytes(ptr, 0, len * sizet);
-                    } else {
-                        // Not so fast!!!
-                        let initial_value_ptr = &initial_value as *const T as *const u8;
-                        for i in 0..len {
-                            copy_nonoverlapping(
-                                initial_value_ptr,
-                                (ptr as *mut u8).add(i * sizet),
-                                sizet,
-                            );
-                        }
-                    }
-                    return Ok(std::slice::from_raw_parts_mut(ptr as *mut T, len));
-                }
+    pub unsafe fn try_realloc(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>> {
+        if new.size() <= old.size() && (ptr.as_ptr() as usize).is_multiple_of(new.align()) {
+            return Ok(ptr);
+        }
+        let kept = old.size().min(new.size());
+        if old.size() > 0 {
+            let chunk = self.chunk(self.current.load(Ordering::Acquire));
+            if let Some(moved) = chunk.rebump(ptr, old.size(), new) {
+                unsafe { ptr::copy(ptr.as_ptr(), moved.as_ptr(), kept) };
+                return Ok(moved);
             }
         }
+        let moved = self.try_alloc_layout(new)?;
+        unsafe { copy_nonoverlapping(ptr.as_ptr(), moved.as_ptr(), kept) };
+        Ok(moved)
     }
 
     /// Allocates space for a str and returns a read-only reference, &str.
@@ -410,7 +1128,8 @@ impl<'a> Bitena<'a> {
     ///
     /// # Example
     ///
-    /// ```
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
     /// use bitena::*;
     ///
     /// fn main() -> Result<()> {
@@ -431,52 +1150,26 @@ impl<'a> Bitena<'a> {
 
     pub fn try_alloc_str(&self, st: &str) -> Result<&str> {
         let sizet = st.len();
-        let align = std::mem::align_of::<u8>();
         if sizet == 0 {
             return Ok::<&str, Error>("");
         }
-        debug_assert!(align.is_power_of_two(), "Alignment must be a power of two");
 
+        let ptr = self.try_alloc_layout(Layout::for_value(st.as_bytes()))?.as_ptr();
         unsafe {
-            loop {
-                let end_byte_idx = self.end_byte_idx.load(Ordering::Relaxed);
-                let ptr_num = (self.buf.as_ptr().add(end_byte_idx as usize) as usize)
-                    .checked_sub(sizet)
-                    .ok_or(Error::PointerUnderflow)?;
-
-                //let ptr = (ptr as usize & !(align - 1)) as *mut u8;  // Align Ptr pre-Miri
-                let ptr = self
-                    .buf
-                    .with_addr(NonZero::new(ptr_num & !(align - 1)).ok_or(Error::PointerUnderflow)?)
-                    .as_ptr() as *mut u8;
-
-                if (ptr as usize) < self.buf.as_ptr() as usize {
-                    return Err(Error::OutOfMemory);
-                }
-                let new_end_byte_idx =
-                    (ptr as usize).saturating_sub(self.buf.as_ptr() as usize) as usize;
-
-                if let Ok(_) = self.end_byte_idx.compare_exchange_weak(
-                    end_byte_idx,     // Expected value
-                    new_end_byte_idx, // New value
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
-                ) {
-                    copy_nonoverlapping(st.as_ptr(), ptr, sizet);
-                    // Unchecked is Ok since the bytes came from a valid str
-                    return Ok(std::str::from_utf8_unchecked(std::slice::from_raw_parts(
-                        ptr, sizet,
-                    )));
-                }
-            }
+            copy_nonoverlapping(st.as_ptr(), ptr, sizet);
+            // Unchecked is Ok since the bytes came from a valid str
+            Ok(core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+                ptr, sizet,
+            )))
         }
     }
 
-    /// Returns the number of bytes remaining in the arena.
+    /// Returns the number of bytes remaining in the arena's current chunk.
     ///
     /// # Example
     ///
-    /// ```rust
+    #[cfg_attr(feature = "alloc", doc = "```rust")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
     /// use bitena::*;
     ///
     /// fn main() {
@@ -486,14 +1179,36 @@ impl<'a> Bitena<'a> {
     /// ```
     #[inline]
     pub fn remaining(&self) -> usize {
-        self.end_byte_idx.load(Ordering::Relaxed)
+        self.chunk(self.current.load(Ordering::Acquire))
+            .end_byte_idx
+            .load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of bytes in all of the arena's chunks.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```rust")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() {
+    ///     let bitena = Bitena::new(1024).unwrap();
+    ///     assert_eq!(bitena.capacity(), 1024);
+    /// }
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.chunk(self.current.load(Ordering::Acquire)).total
     }
 
     /// Resets the arena, making all previously allocated memory available again.
+    /// A growable arena frees all of its grown chunks, keeping only the first.
     ///
     /// # Example
     ///
-    /// ```rust
+    #[cfg_attr(feature = "alloc", doc = "```rust")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
     /// use bitena::*;
     ///
     /// fn main() -> Result<()> {
@@ -506,54 +1221,243 @@ impl<'a> Bitena<'a> {
     /// }
     /// ```
     pub fn reset(&mut self) {
-        loop {
-            let end_byte_idx = self.end_byte_idx.load(Ordering::Relaxed);
-
-            if let Ok(_) = self.end_byte_idx.compare_exchange_weak(
-                end_byte_idx,       // Expected value
-                self.layout.size(), // New value
-                Ordering::Relaxed,
-                Ordering::Relaxed,
-            ) {
-                return ();
+        if self.zero_on_reset {
+            self.zero_used();
+        }
+        free_grown_chunks(mem::replace(self.current.get_mut(), ptr::null_mut()), ptr::null_mut());
+        self.head.reset();
+        self.generation = next_generation();
+        self.resets += 1;
+    }
+
+    /// Resets the arena like `reset`, then gives the memory of the first chunk back
+    /// to the OS, apart from the `retain` bytes that will be reused first, so a
+    /// long running service doesn't hold on to its peak memory use.
+    ///
+    /// The address space is kept, and trimmed pages come back as they are used again.
+    /// Memory from `from_buffer` or `from_raw_parts` is never trimmed, and on
+    /// platforms other than Linux only the grown chunks are freed.
+    ///
+    /// Heap chunks are trimmed in place, which assumes the global allocator hands
+    /// out ordinary private memory, as the system allocator does. What trimmed
+    /// pages hold when they come back isn't specified, so don't rely on zeros.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```rust")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let mut bitena = Bitena::new(64 << 20)?;
+    ///     bitena.try_alloc_slice(1u8, 64 << 20)?; // A big job
+    ///     bitena.reset_and_trim(1 << 20); // Keep 1MB ready for the next one
+    ///     assert_eq!(bitena.remaining(), 64 << 20);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn reset_and_trim(&mut self, retain: usize) {
+        self.reset();
+        self.head.trim(retain);
+    }
+
+    /// Returns the arena's current generation. It changes on every `reset` or `rewind`,
+    /// and is unique across all arenas, so `GenHandle`s can detect stale use.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns a checkpoint recording how much of the arena is in use right now.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "alloc", doc = "```rust")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// use bitena::*;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let mut bitena = Bitena::new(1024)?;
+    ///     bitena.try_alloc(1u32)?;
+    ///     let checkpoint = bitena.checkpoint();
+    ///     bitena.try_alloc_slice(0u8, 100)?;
+    ///     assert_eq!(bitena.remaining(), 920);
+    ///     bitena.rewind(checkpoint)?;
+    ///     assert_eq!(bitena.remaining(), 1020);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn checkpoint(&self) -> Checkpoint {
+        let current = self.current.load(Ordering::Acquire);
+        Checkpoint {
+            arena: self.head.buf.as_ptr() as usize,
+            resets: self.resets,
+            chunk: current as usize,
+            end_byte_idx: self.chunk(current).end_byte_idx.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Rewinds the arena to `checkpoint`, releasing everything allocated since it
+    /// was taken, including any chunks grown since then.
+    /// Like `reset`, it starts a new generation, so every `GenHandle` goes stale,
+    /// even those allocated before the checkpoint.
+    ///
+    /// Returns `Error::InvalidCheckpoint`, leaving the arena untouched, if the
+    /// checkpoint came from another arena, was taken before the last reset, or lies
+    /// ahead of the arena's current position, as happens after rewinding to an
+    /// earlier checkpoint.
+    pub fn rewind(&mut self, checkpoint: Checkpoint) -> Result<()> {
+        if checkpoint.arena != self.head.buf.as_ptr() as usize || checkpoint.resets != self.resets {
+            return Err(Error::InvalidCheckpoint);
+        }
+
+        // Find the checkpoint's chunk before freeing anything
+        let target = checkpoint.chunk as *mut Chunk;
+        let mut current = *self.current.get_mut();
+        while current != target {
+            if current.is_null() {
+                return Err(Error::InvalidCheckpoint);
+            }
+            current = self.chunk(current).prev;
+        }
+        let chunk = self.chunk(target);
+        if checkpoint.end_byte_idx < chunk.end_byte_idx.load(Ordering::Relaxed)
+            || checkpoint.end_byte_idx > chunk.layout.size()
+        {
+            return Err(Error::InvalidCheckpoint);
+        }
+
+        if self.zero_on_reset {
+            // Freed chunks won't be around for the next reset to zero
+            let mut current = *self.current.get_mut();
+            while current != target {
+                let chunk = unsafe { &mut *current };
+                chunk.zero_used();
+                current = chunk.prev;
             }
         }
+        free_grown_chunks(mem::replace(self.current.get_mut(), target), target);
+        let chunk = if target.is_null() { &mut self.head } else { unsafe { &mut *target } };
+        chunk.rewind(checkpoint.end_byte_idx);
+        self.generation = next_generation();
+        Ok(())
+    }
+
+    /// Returns a guard that rewinds the arena to its current position when dropped.
+    ///
+    /// See `BitenaScope` for an example.
+    #[inline]
+    pub fn scope(&mut self) -> BitenaScope<'_, 'a> {
+        BitenaScope::new(self)
+    }
+
+    /// Zeroes every byte handed out, in every chunk.
+    fn zero_used(&mut self) {
+        let mut current = *self.current.get_mut();
+        while !current.is_null() {
+            let chunk = unsafe { &mut *current };
+            chunk.zero_used();
+            current = chunk.prev;
+        }
+        self.head.zero_used();
+    }
+
+    /// Returns the chunk a current pointer refers to, null being the head chunk.
+    #[inline]
+    fn chunk(&self, current: *mut Chunk) -> &Chunk {
+        if current.is_null() {
+            &self.head
+        } else {
+            unsafe { &*current }
+        }
+    }
+
+    /// Gives back the most recent allocation, of `layout` at `ptr`, if nothing
+    /// else has been allocated since. Returns whether it was given back.
+    #[inline]
+    pub(crate) fn unbump(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        self.chunk(self.current.load(Ordering::Acquire))
+            .unbump(ptr, layout.size())
+    }
+
+    /// Chains a new chunk, big enough for at least `layout`, onto `current`.
+    ///
+    /// Growing is lock-free too: if another thread publishes its own chunk first,
+    /// ours is freed and the caller simply retries in the winning chunk.
+    #[cold]
+    #[cfg(feature = "alloc")]
+    fn grow(&self, current: *mut Chunk, layout: Layout) -> Result<()> {
+        let chunk = self.chunk(current);
+        let next_size = match self.growth {
+            Growth::Fixed => return Err(Error::OutOfMemory),
+            Growth::Double => chunk.layout.size().saturating_mul(2),
+            Growth::Step(step) => step,
+        };
+        let room = self.max_capacity.saturating_sub(chunk.total);
+        if layout.size() > room {
+            return Err(Error::OutOfMemory);
+        }
+        // An aligned chunk always fits an aligned allocation of its own size
+        let size = next_size.max(layout.size()).min(room).max(1);
+        let layout = Layout::from_size_align(size, layout.align().max(self.head.layout.align()))?;
+        let grown = match self.backing {
+            Backing::Heap => Chunk::new(layout, current, chunk.total + size)?,
+            #[cfg(target_os = "linux")]
+            Backing::Reserved => Chunk::reserve(layout, current, chunk.total)?,
+        };
+        if self.huge_pages {
+            grown.advise_huge_pages();
+        }
+        let grown = alloc::boxed::Box::into_raw(alloc::boxed::Box::new(grown));
+
+        if self
+            .current
+            .compare_exchange(current, grown, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            drop(unsafe { alloc::boxed::Box::from_raw(grown) });
+        }
+        Ok(())
+    }
+
+    /// Without the `alloc` feature there's no memory to grow into.
+    #[cold]
+    #[cfg(not(feature = "alloc"))]
+    fn grow(&self, _current: *mut Chunk, _layout: Layout) -> Result<()> {
+        Err(Error::OutOfMemory)
     }
 }
 
 impl Drop for Bitena<'_> {
     #[inline]
     fn drop(&mut self) {
-        unsafe {
-            dealloc(self.buf.as_ptr(), self.layout);
-        }
+        // The head chunk frees itself
+        free_grown_chunks(*self.current.get_mut(), ptr::null_mut());
     }
 }
 
 unsafe impl Send for Bitena<'_> {}
 unsafe impl Sync for Bitena<'_> {}
 
-/// Returns IF value is comprised of all zeros.
-#[inline]
-fn is_all_zeros<T>(value: &T) -> bool {
-    let num_bytes = std::mem::size_of::<T>();
-    unsafe {
-        let ptr = value as *const T as *const u8;
-        for i in 0..num_bytes {
-            if *ptr.add(i) != 0 {
-                return false;
-            }
-        }
+/// Fails to compile for types with drop glue, which a Bitena would silently leak.
+#[inline(always)]
+pub(crate) fn assert_no_drop<T>() {
+    const {
+        assert!(
+            !mem::needs_drop::<T>(),
+            "Bitena never drops its items. Use a DropBitena, or alloc_leaking() to leak on purpose."
+        )
     }
-    true
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod test {
     use super::*;
+    use std::num::NonZero;
     use sysinfo::{Pid, System};
 
     #[test]
+    #[allow(clippy::modulo_one)]
     fn test_try_alignment() -> Result<()> {
         let bitena = Bitena::new(1024)?;
 
@@ -711,6 +1615,339 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_try_alloc_leaking() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+        let v = bitena.try_alloc_leaking(vec![1u32, 2, 3])?;
+        v.push(4);
+        assert_eq!(v.iter().sum::<u32>(), 10);
+        // Drop by hand, so the test doesn't actually leak
+        unsafe { std::ptr::drop_in_place(v) };
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_slice_fills() -> Result<()> {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Id(u32);
+
+        let bitena = Bitena::new(1024)?;
+
+        let copies = bitena.try_alloc_slice_copy(0x0102u16, 3)?;
+        assert_eq!(copies, [0x0102, 0x0102, 0x0102]);
+        let padded = bitena.try_alloc_slice_copy((0u8, 0u32), 3)?; // Padding is never read
+        assert_eq!(padded, [(0, 0); 3]);
+
+        let clones = bitena.try_alloc_slice_clone(Id(9), 3)?;
+        clones[2].0 = 10;
+        assert_eq!(clones, [Id(9), Id(9), Id(10)]);
+
+        let filled = bitena.try_alloc_slice_fill_with(4, |i| Id(i as u32 * 2))?;
+        assert_eq!(filled, [Id(0), Id(2), Id(4), Id(6)]);
+
+        assert!(matches!(
+            bitena.try_alloc_slice_fill_with(1000, |i| i as u64),
+            Err(Error::OutOfMemory)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_slice_from() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+
+        let src = [1u64, 2, 3, 4];
+        let copy = bitena.try_alloc_slice_from_slice(&src)?;
+        copy[0] = 5;
+        assert_eq!(copy, [5, 2, 3, 4]);
+        assert_eq!(copy.as_ptr() as usize % 8, 0);
+
+        let collected = bitena.try_alloc_slice_from_iter(src.iter().map(|n| *n as u8 + 1))?;
+        assert_eq!(collected, [2, 3, 4, 5]);
+
+        // An iterator that reports more items than it yields
+        struct Liar(u32);
+        impl Iterator for Liar {
+            type Item = u32;
+            fn next(&mut self) -> Option<u32> {
+                self.0 += 1;
+                (self.0 <= 2).then_some(self.0)
+            }
+        }
+        impl ExactSizeIterator for Liar {
+            fn len(&self) -> usize {
+                10
+            }
+        }
+        assert_eq!(bitena.try_alloc_slice_from_iter(Liar(0))?, [1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_slice_from_iter_panic() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+        let result = std::panic::catch_unwind(|| {
+            bitena.alloc_slice_from_iter((0..8u32).map(|i| if i == 4 { panic!("Boom") } else { i }))
+                .len()
+        });
+        assert!(result.is_err());
+        // The reservation is lost, but the arena is still usable
+        assert_eq!(bitena.remaining(), 1024 - 32);
+        assert_eq!(bitena.try_alloc_slice_from_iter(0..3u32)?, [0, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_alloc_with() -> Result<()> {
+        let bitena = Bitena::new(128 * 1024)?;
+
+        let big = bitena.try_alloc_with(|| [7u64; 8 * 1024])?;
+        assert!(big.iter().all(|&n| n == 7));
+        assert_eq!(big.as_ptr() as usize % 8, 0);
+        let remaining = bitena.remaining();
+
+        let ok = bitena.try_alloc_try_with(|| Ok::<u32, ()>(5));
+        assert_eq!(*ok.unwrap(), 5);
+        assert_eq!(bitena.remaining(), remaining - 4);
+
+        let err = bitena.try_alloc_try_with(|| Err::<u32, _>("Bad input"));
+        assert!(matches!(err, Err(AllocOrInitError::Init("Bad input"))));
+        assert_eq!(bitena.remaining(), remaining - 4, "Failed init should roll back");
+
+        // Rollback isn't possible once another allocation lands in between
+        let err = bitena.try_alloc_try_with(|| {
+            bitena.alloc(1u8);
+            Err::<u32, _>(())
+        });
+        assert!(err.is_err());
+        assert_eq!(bitena.remaining(), remaining - 9);
+
+        let full = Bitena::new(2)?;
+        assert!(matches!(
+            full.try_alloc_try_with(|| Ok::<u32, ()>(5)),
+            Err(AllocOrInitError::Alloc(Error::OutOfMemory))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_uninit_and_zeroed() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+
+        let slot = bitena.try_alloc_uninit::<u32>()?;
+        assert_eq!(slot.as_ptr() as usize % 4, 0);
+        assert_eq!(*slot.write(9), 9);
+
+        let buf = bitena.try_alloc_uninit_slice::<u64>(10)?;
+        assert_eq!(buf.len(), 10);
+        assert_eq!(buf.as_ptr() as usize % 8, 0);
+        assert!(bitena.remaining() <= 1024 - 4 - 80);
+
+        // Dirty the memory, then check zeroing after a reset
+        let mut bitena = bitena;
+        bitena.reset();
+        bitena.try_alloc_slice(0xffu8, 1024)?;
+        bitena.reset();
+        let zeros = bitena.try_alloc_zeroed_slice::<Option<NonZero<u32>>>(8)?;
+        assert!(zeros.iter().all(|z| z.is_none()));
+        let zeros = bitena.try_alloc_zeroed_slice::<[f64; 2]>(8)?;
+        assert!(zeros.iter().all(|z| *z == [0.0, 0.0]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_alloc_layout() -> Result<()> {
+        let bitena = Bitena::with_growth(64, Growth::Step(64), None)?;
+
+        for align in [1, 2, 8, 64, 4096] {
+            let layout = Layout::from_size_align(24, align).unwrap();
+            let ptr = bitena.try_alloc_layout(layout)?;
+            assert_eq!(ptr.as_ptr() as usize % align, 0, "{align} byte alignment");
+            unsafe { ptr::write_bytes(ptr.as_ptr(), 0xab, 24) };
+        }
+
+        let fixed = Bitena::new(64)?;
+        let layout = Layout::from_size_align(65, 1).unwrap();
+        assert!(matches!(fixed.try_alloc_layout(layout), Err(Error::OutOfMemory)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_zero_sized() -> Result<()> {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Marker;
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #[repr(align(64))]
+        struct AlignedMarker;
+
+        let bitena = Bitena::new(16)?;
+
+        let marker = bitena.try_alloc(Marker)?;
+        assert_eq!(*marker, Marker);
+        let aligned = bitena.try_alloc(AlignedMarker)?;
+        assert_eq!(aligned as *mut AlignedMarker as usize % 64, 0);
+        assert_eq!(bitena.try_alloc_with(|| ())?, &mut ());
+
+        let markers = bitena.try_alloc_slice(Marker, 1000)?;
+        assert_eq!(markers.len(), 1000);
+        let aligned = bitena.try_alloc_slice_fill_with(3, |_| AlignedMarker)?;
+        assert_eq!(aligned.as_ptr() as usize % 64, 0);
+
+        let empty = bitena.try_alloc_slice(0u64, 0)?;
+        assert!(empty.is_empty());
+        assert_eq!(empty.as_ptr() as usize % 8, 0);
+        assert!(bitena.try_alloc_slice_from_slice::<u32>(&[])?.is_empty());
+        assert!(bitena.try_alloc_zeroed_slice::<u128>(0)?.is_empty());
+        assert!(bitena.try_alloc_uninit_slice::<u16>(0)?.is_empty());
+
+        assert_eq!(bitena.remaining(), 16, "Zero sized allocations should be free");
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_realloc() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+        let small = Layout::array::<u32>(4).unwrap();
+        let large = Layout::array::<u32>(16).unwrap();
+
+        let ptr = bitena.try_alloc_layout(small)?.cast::<u32>();
+        unsafe { ptr.as_ptr().copy_from([1u32, 2, 3, 4].as_ptr(), 4) };
+
+        // Most recent allocation grows in place
+        let grown = unsafe { bitena.try_realloc(ptr.cast(), small, large)? }.cast::<u32>();
+        assert_eq!(bitena.remaining(), 1024 - 64);
+        assert_eq!(unsafe { std::slice::from_raw_parts(grown.as_ptr(), 4) }, [1, 2, 3, 4]);
+
+        // Anything else is copied
+        bitena.try_alloc(0u8)?;
+        let huge = Layout::array::<u32>(32).unwrap();
+        let moved = unsafe { bitena.try_realloc(grown.cast(), large, huge)? }.cast::<u32>();
+        assert_ne!(moved, grown);
+        assert_eq!(moved.as_ptr() as usize % 4, 0);
+        assert_eq!(unsafe { std::slice::from_raw_parts(moved.as_ptr(), 4) }, [1, 2, 3, 4]);
+
+        // Shrinking never moves
+        let shrunk = unsafe { bitena.try_realloc(moved.cast(), huge, small)? };
+        assert_eq!(shrunk, moved.cast());
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_growth() -> Result<()> {
+        let mut bitena = Bitena::with_growth(64, Growth::Double, None)?;
+
+        let first = bitena.try_alloc_slice(1u8, 60)?;
+        let second = bitena.try_alloc_slice(2u8, 60)?;
+        assert_eq!(bitena.capacity(), 192, "Second chunk should double the first");
+        assert_eq!(bitena.remaining(), 68);
+
+        let large = bitena.try_alloc_slice(3u64, 100)?;
+        assert_eq!(bitena.capacity(), 192 + 800, "Oversized requests get their own chunk");
+        assert_eq!(large.as_ptr() as usize % 8, 0);
+
+        assert!(first.iter().all(|&b| b == 1));
+        assert!(second.iter().all(|&b| b == 2));
+        assert!(large.iter().all(|&n| n == 3));
+
+        bitena.reset();
+        assert_eq!(bitena.capacity(), 64, "Reset should keep only the first chunk");
+        assert_eq!(bitena.remaining(), 64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_growth_max_capacity() -> Result<()> {
+        let bitena = Bitena::with_growth(64, Growth::Step(64), Some(160))?;
+
+        bitena.try_alloc_slice(0u8, 64)?;
+        bitena.try_alloc_slice(0u8, 64)?;
+        assert_eq!(bitena.capacity(), 128);
+
+        // Only 32 bytes of growth are left under the cap
+        assert!(matches!(bitena.try_alloc_slice(0u8, 33), Err(Error::OutOfMemory)));
+        bitena.try_alloc_slice(0u8, 32)?;
+        assert_eq!(bitena.capacity(), 160);
+        assert!(matches!(bitena.try_alloc(0u8), Err(Error::OutOfMemory)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_growth_threads() -> Result<()> {
+        let bitena = Bitena::with_growth(128, Growth::Step(256), None)?;
+
+        std::thread::scope(|s| {
+            for t in 0..4u64 {
+                let bitena = &bitena;
+                s.spawn(move || {
+                    let nums: Vec<&mut u64> = (0..500).map(|i| bitena.alloc(t * 1000 + i)).collect();
+                    for (i, num) in nums.iter().enumerate() {
+                        assert_eq!(**num, t * 1000 + i as u64);
+                    }
+                });
+            }
+        });
+        assert!(bitena.capacity() >= 4 * 500 * 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_rewind() -> Result<()> {
+        let mut bitena = Bitena::with_growth(64, Growth::Double, None)?;
+
+        let start = bitena.checkpoint();
+        bitena.try_alloc_slice(1u8, 32)?;
+        let middle = bitena.checkpoint();
+        bitena.try_alloc_slice(2u8, 100)?;
+        assert_eq!(bitena.capacity(), 64 + 128);
+
+        bitena.rewind(middle)?;
+        assert_eq!(bitena.capacity(), 64, "Rewind should free chunks grown after the checkpoint");
+        assert_eq!(bitena.remaining(), 32);
+
+        // Rewinding forward past the arena's current position is refused
+        bitena.rewind(start)?;
+        assert!(matches!(bitena.rewind(middle), Err(Error::InvalidCheckpoint)));
+        assert_eq!(bitena.remaining(), 64);
+
+        // So is a checkpoint from before a reset, even once the arena is past it again
+        bitena.try_alloc_slice(1u8, 32)?;
+        let before_reset = bitena.checkpoint();
+        bitena.reset();
+        bitena.try_alloc_slice(3u8, 48)?;
+        assert!(matches!(bitena.rewind(before_reset), Err(Error::InvalidCheckpoint)));
+        assert_eq!(bitena.remaining(), 16);
+
+        let other = Bitena::new(64)?;
+        assert!(matches!(bitena.rewind(other.checkpoint()), Err(Error::InvalidCheckpoint)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_scope() -> Result<()> {
+        let mut bitena = Bitena::new(1024)?;
+        let kept = bitena.try_alloc(7u32)? as *mut u32;
+        {
+            let mut outer = bitena.scope();
+            outer.try_alloc_slice(0u8, 100)?;
+            {
+                let inner = outer.scope();
+                inner.try_alloc_slice(0u8, 200)?;
+                assert_eq!(inner.remaining(), 720);
+            }
+            assert_eq!(outer.remaining(), 920);
+        }
+        assert_eq!(bitena.remaining(), 1020);
+        assert_eq!(unsafe { *kept }, 7);
+
+        {
+            let mut scope = bitena.scope();
+            scope.try_alloc(0u64)?;
+            scope.reset();
+        }
+        assert_eq!(bitena.remaining(), 1024, "A reset inside a scope should stick");
+        Ok(())
+    }
+
     fn format_number(n: u64) -> String {
         let s = n.to_string();
         let mut result = String::new();
@@ -749,6 +1986,89 @@ mod test {
     }
 
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_try_reserved_commits_lazily() -> Result<()> {
+        const RESERVED: usize = 16 << 30; // 16GB
+        let step = os::commit_step();
+        let bitena = Bitena::with_reserved(RESERVED)?;
+        let committed = || bitena.head.committed.load(Ordering::Relaxed);
+        assert_eq!(bitena.capacity(), RESERVED);
+        assert_eq!(committed(), RESERVED, "Nothing is committed up front");
+
+        let small = bitena.try_alloc(7u64)?;
+        assert_eq!(committed(), RESERVED - step);
+        let large = bitena.try_alloc_slice(1u8, 3 * step)?;
+        assert_eq!(committed(), RESERVED - 4 * step);
+        large.fill(2);
+        *small += 1;
+
+        // Growing in place commits too
+        let (old, new) = (Layout::array::<u8>(3 * step)?, Layout::array::<u8>(5 * step)?);
+        let grown = unsafe { bitena.try_realloc(NonNull::from(large).cast(), old, new)? };
+        unsafe { grown.write_bytes(3, 5 * step) };
+        assert_eq!(committed(), RESERVED - 6 * step);
+        assert_eq!(*small, 8);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_try_reserved_threads() -> Result<()> {
+        const RESERVED: usize = 1 << 30;
+        let step = os::commit_step();
+        let bitena = Bitena::with_reserved(RESERVED)?;
+
+        // Threads racing to commit must never see another's commit as a failure
+        std::thread::scope(|s| {
+            for t in 0..8u8 {
+                let bitena = &bitena;
+                s.spawn(move || {
+                    for i in 0..300 {
+                        // Sizes up to a commit step, so threads often need the same pages
+                        let slice = bitena.alloc_slice(t, 1 + (i * 7919) % step);
+                        assert!(slice.iter().all(|&b| b == t));
+                    }
+                });
+            }
+        });
+        assert!(bitena.head.committed.load(Ordering::Relaxed) <= bitena.head.end_byte_idx.load(Ordering::Relaxed));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_try_reset_and_trim() -> Result<()> {
+        const SIZE: usize = 8 << 20;
+        const RETAIN: usize = 1 << 20;
+        for (mut bitena, reserved) in [(Bitena::new(SIZE)?, false), (Bitena::with_reserved(SIZE)?, true)] {
+            bitena.try_alloc_slice(0xFFu8, SIZE)?;
+            bitena.reset_and_trim(RETAIN);
+            assert_eq!(bitena.remaining(), SIZE);
+
+            // Retained pages keep their bytes. Trimmed ones read back as zeros,
+            // though only the arena's own mapping promises that.
+            let byte = |i: usize| unsafe { *bitena.head.buf.as_ptr().add(i) };
+            let page = os::page_size();
+            if reserved {
+                assert_eq!(byte(SIZE / 2), 0);
+                assert_eq!(byte(SIZE - RETAIN - page), 0);
+            }
+            assert_eq!(byte(SIZE - RETAIN + page), 0xFF);
+            assert_eq!(byte(SIZE - 1), 0xFF);
+
+            let slice = bitena.try_alloc_slice(2u8, SIZE)?;
+            assert!(slice.iter().all(|&b| b == 2));
+        }
+
+        let mut buf = [1u8; 8192];
+        let mut bitena = unsafe { Bitena::from_raw_parts(NonNull::from(&mut buf).cast(), 8192) };
+        bitena.reset_and_trim(0);
+        drop(bitena);
+        assert!(buf.iter().all(|&b| b == 1), "Borrowed memory is never trimmed");
+        Ok(())
+    }
+
     // Note, Miri fails the sysconf(_SC_CLK_TCK) call.
     #[cfg_attr(miri, cfg(miri_skip))]
     #[test]
@@ -793,3 +2113,71 @@ mod test {
         Ok(())
     }
 }
+
+// Tests that need no allocator, so they run with default-features = false too
+#[cfg(test)]
+mod test_from_buffer {
+    use super::*;
+
+    #[test]
+    fn test_try_from_buffer() -> Result<()> {
+        let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+        let range = buf.as_ptr_range();
+        let mut bitena = Bitena::from_buffer(&mut buf);
+        let num = bitena.try_alloc(7u64)?;
+        assert!(range.contains(&(num as *mut u64 as *const MaybeUninit<u8>)));
+        assert!(matches!(bitena.try_alloc([0u8; 64]), Err(Error::OutOfMemory)));
+        bitena.reset();
+        assert_eq!(bitena.remaining(), 64);
+        drop(bitena);
+
+        // Memory from another allocator is left for it to free
+        let mut vec = Vec::<u64>::with_capacity(8);
+        let bitena = unsafe { Bitena::from_raw_parts(NonNull::new(vec.as_mut_ptr()).unwrap().cast(), 64) };
+        bitena.try_alloc_slice(9u64, 8)?;
+        drop(bitena);
+        unsafe { vec.set_len(8) };
+        assert_eq!(vec, [9; 8]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_core_allocs() -> Result<()> {
+        let mut buf = [MaybeUninit::<u8>::uninit(); 256];
+        let bitena = Bitena::from_buffer(&mut buf);
+
+        let num = bitena.try_alloc(7u32)?;
+        let slice = bitena.try_alloc_slice_copy(1u16, 4)?;
+        let st = bitena.try_alloc_str("Hello")?;
+        let zeros = bitena.try_alloc_zeroed_slice::<u64>(4)?;
+        *num += 1;
+        slice[3] = 2;
+
+        assert_eq!(*num, 8);
+        assert_eq!(slice, [1, 1, 1, 2]);
+        assert_eq!(st, "Hello");
+        assert_eq!(zeros, [0; 4]);
+        assert_eq!(zeros.as_ptr() as usize % 8, 0);
+        assert!(matches!(bitena.try_alloc([0u8; 256]), Err(Error::OutOfMemory)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_core_scopes_and_handles() -> Result<()> {
+        let mut buf = [MaybeUninit::<u8>::uninit(); 256];
+        let mut bitena = Bitena::from_buffer(&mut buf);
+
+        let handle = bitena.try_alloc_gen_handle(5u64)?;
+        {
+            let scope = bitena.scope();
+            scope.try_alloc_slice(0u8, 100)?;
+            assert_eq!(scope.remaining(), 256 - 8 - 100);
+        }
+        assert_eq!(bitena.remaining(), 256 - 8);
+        assert!(bitena.get_gen(handle).is_none(), "Rewinding starts a new generation");
+
+        let handle = bitena.try_alloc_handle(6u64)?;
+        assert_eq!(unsafe { *bitena.get(handle) }, 6);
+        Ok(())
+    }
+}
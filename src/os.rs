@@ -0,0 +1,95 @@
+//! Virtual memory straight from the OS, for arenas created with `Bitena::with_reserved`,
+//! and for giving memory back with `Bitena::reset_and_trim`.
+//!
+//! Address space is reserved with no access, which costs no memory, and pages are
+//! committed, made readable and writable, as the arena fills. Even committed pages
+//! only take up memory once they are touched.
+
+use core::ptr::{self, NonNull};
+
+/// Reserved pages are committed at least this many bytes at a time, to keep
+/// the number of mprotect calls down.
+const COMMIT_STEP: usize = 64 * 1024;
+
+#[inline]
+pub(crate) fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Returns how many bytes at a time to commit, a multiple of the page size.
+#[inline]
+pub(crate) fn commit_step() -> usize {
+    COMMIT_STEP.max(page_size())
+}
+
+/// Reserves `len` bytes of address space aligned to `align`, a multiple of the
+/// page size, without committing any of it.
+pub(crate) fn reserve(len: usize, align: usize) -> Option<NonNull<u8>> {
+    // Over reserve, then give back the unaligned ends
+    let padded = len.checked_add(align - page_size())?;
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            padded,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE,
+            -1,
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return None;
+    }
+    let ptr = ptr.cast::<u8>();
+    let head = (ptr as usize).next_multiple_of(align) - ptr as usize;
+    let tail = padded - head - len;
+    unsafe {
+        if head > 0 {
+            libc::munmap(ptr.cast(), head);
+        }
+        if tail > 0 {
+            libc::munmap(ptr.add(head + len).cast(), tail);
+        }
+        NonNull::new(ptr.add(head))
+    }
+}
+
+/// Makes `len` reserved bytes at `ptr` readable and writable. Returns false when
+/// the OS refuses, which usually means it's out of memory.
+///
+/// # Safety
+///
+/// `ptr` must be page aligned, and the range must lie within a reservation.
+pub(crate) unsafe fn commit(ptr: NonNull<u8>, len: usize) -> bool {
+    unsafe { libc::mprotect(ptr.as_ptr().cast(), len, libc::PROT_READ | libc::PROT_WRITE) == 0 }
+}
+
+/// Lets the OS reclaim the memory behind `len` bytes at `ptr`, keeping the pages
+/// mapped. They read as zeros once touched again.
+///
+/// # Safety
+///
+/// `ptr` must be page aligned, and nothing may rely on the contents of the range.
+pub(crate) unsafe fn discard(ptr: NonNull<u8>, len: usize) {
+    unsafe { libc::madvise(ptr.as_ptr().cast(), len, libc::MADV_DONTNEED) };
+}
+
+/// Advises the OS to back `len` bytes at `ptr` with transparent huge pages.
+/// It's only advice, so failures, such as huge pages being disabled, are ignored.
+///
+/// # Safety
+///
+/// `ptr` must be page aligned, and the range must be mapped.
+#[cfg(feature = "alloc")]
+pub(crate) unsafe fn advise_huge_pages(ptr: NonNull<u8>, len: usize) {
+    unsafe { libc::madvise(ptr.as_ptr().cast(), len, libc::MADV_HUGEPAGE) };
+}
+
+/// Returns a reservation to the OS.
+///
+/// # Safety
+///
+/// `ptr` and `len` must be exactly those of a reservation that is no longer used.
+pub(crate) unsafe fn release(ptr: NonNull<u8>, len: usize) {
+    unsafe { libc::munmap(ptr.as_ptr().cast(), len) };
+}
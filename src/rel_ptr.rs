@@ -0,0 +1,207 @@
+use core::fmt;
+use core::marker::PhantomData;
+use core::ptr;
+
+/// ArenaRelPtr
+///
+/// A pointer stored as a signed byte offset from its own address. Structures
+/// linked with relative pointers stay valid when the whole buffer they live in
+/// is copied, written to disk and read back, or mapped at a different address,
+/// as long as the pointer and its target move together.
+///
+/// Null is stored as `isize::MIN`, an offset no target in the same buffer can
+/// have, so a pointer may point at itself. Since moving an ArenaRelPtr on its
+/// own breaks it, it is neither `Copy` nor `Clone`, and is set in place once it
+/// sits in the arena.
+///
+/// # Example
+///
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// use bitena::*;
+///
+/// struct Node {
+///     value: u32,
+///     next: ArenaRelPtr<Node>,
+/// }
+///
+/// fn main() -> Result<()> {
+///     let bitena = Bitena::new(1024)?;
+///     let tail = bitena.try_alloc(Node { value: 2, next: ArenaRelPtr::null() })?;
+///     let head = bitena.try_alloc(Node { value: 1, next: ArenaRelPtr::null() })?;
+///     head.next.set(tail);
+///
+///     let next = unsafe { head.next.as_ref() }.unwrap();
+///     assert_eq!(next.value, 2);
+///     assert!(next.next.is_null());
+///     Ok(())
+/// }
+/// ```
+///
+/// Targets are found from their address alone, through provenance that `set`
+/// exposes. After moving the buffer, expose the new buffer's provenance with
+/// `expose_provenance` before following any pointers in it.
+#[repr(transparent)]
+pub struct ArenaRelPtr<T> {
+    offset: isize,
+    _marker: PhantomData<*mut T>, // Send and Sync are implemented below
+}
+
+impl<T> ArenaRelPtr<T> {
+    const NULL: isize = isize::MIN;
+
+    #[inline]
+    pub const fn null() -> Self {
+        Self {
+            offset: Self::NULL,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.offset == Self::NULL
+    }
+
+    /// Returns the byte offset from this pointer's own address to its target,
+    /// or `isize::MIN` if the pointer is null.
+    #[inline]
+    pub fn offset(&self) -> isize {
+        self.offset
+    }
+
+    /// Points at `target`, which should live in the same buffer as this pointer.
+    #[inline]
+    pub fn set(&mut self, target: *mut T) {
+        // The target's provenance is exposed, so as_ptr can recover it from the address alone
+        let target = target.expose_provenance();
+        self.offset = target.wrapping_sub(self.addr()) as isize;
+    }
+
+    #[inline]
+    pub fn set_null(&mut self) {
+        self.offset = Self::NULL;
+    }
+
+    /// Returns the raw pointer to the target, or a null pointer.
+    #[inline]
+    pub fn as_ptr(&self) -> *mut T {
+        if self.is_null() {
+            return ptr::null_mut();
+        }
+        ptr::with_exposed_provenance_mut(self.addr().wrapping_add_signed(self.offset))
+    }
+
+    /// Returns a reference to the target, or None if the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// The target must still be at the same offset from this pointer, that is, the
+    /// pointer and its target must only ever have moved together, and be initialized.
+    /// The provenance of the memory the target now lives in must have been exposed,
+    /// which `set` does, but a buffer the target was moved to has to be exposed by
+    /// the caller, with `expose_provenance`.
+    #[inline]
+    pub unsafe fn as_ref(&self) -> Option<&T> {
+        unsafe { self.as_ptr().as_ref() }
+    }
+
+    /// Returns a mutable reference to the target, or None if the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// The same as for `as_ref`, and nothing else may reference the target.
+    #[inline]
+    pub unsafe fn as_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.as_ptr().as_mut() }
+    }
+
+    #[inline]
+    fn addr(&self) -> usize {
+        (self as *const Self).addr()
+    }
+}
+
+impl<T> Default for ArenaRelPtr<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<T> fmt::Debug for ArenaRelPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_null() {
+            write!(f, "ArenaRelPtr(null)")
+        } else {
+            write!(f, "ArenaRelPtr({:+})", self.offset)
+        }
+    }
+}
+
+// Like &mut T, it hands out shared and exclusive access to its target
+unsafe impl<T: Send> Send for ArenaRelPtr<T> {}
+unsafe impl<T: Sync> Sync for ArenaRelPtr<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Bitena, Result};
+    use core::mem::MaybeUninit;
+
+    struct Node {
+        value: usize,
+        next: ArenaRelPtr<Node>,
+    }
+
+    fn sum(mut node: &Node) -> usize {
+        let mut total = node.value;
+        while let Some(next) = unsafe { node.next.as_ref() } {
+            total += next.value;
+            node = next;
+        }
+        total
+    }
+
+    #[test]
+    fn test_try_survives_memcpy() -> Result<()> {
+        let mut buf = [MaybeUninit::<u8>::uninit(); 1024];
+        let bitena = Bitena::from_buffer(&mut buf);
+        let nodes = bitena.try_alloc_slice_fill_with(4, |value| Node {
+            value,
+            next: ArenaRelPtr::null(),
+        })?;
+        // Link out of order, so offsets are both positive and negative
+        let base = nodes.as_mut_ptr();
+        unsafe {
+            nodes[0].next.set(base.add(3));
+            nodes[3].next.set(base.add(1));
+            nodes[1].next.set(base.add(2));
+        }
+        assert!(nodes[0].next.offset() > 0 && nodes[3].next.offset() < 0);
+        assert_eq!(sum(&nodes[0]), 6);
+
+        // Pointing at itself is offset 0, which isn't null
+        let itself = bitena.try_alloc(ArenaRelPtr::<u8>::null())?;
+        let addr = itself as *mut _ as *mut u8;
+        itself.set(addr);
+        assert_eq!(itself.offset(), 0);
+        assert!(!itself.is_null() && itself.as_ptr() == addr);
+
+        // Move the whole structure to a different address
+        let copy = bitena.try_alloc_uninit_slice::<Node>(4)?;
+        unsafe {
+            ptr::copy_nonoverlapping(nodes.as_ptr(), copy.as_mut_ptr().cast(), 4);
+            nodes[0].value = 100;
+            copy.as_mut_ptr().expose_provenance(); // Required before following pointers in a moved buffer
+            let copy = &mut *(copy as *mut _ as *mut [Node]);
+            assert_eq!(sum(&copy[0]), 6, "The copy links to itself, not the original");
+
+            copy[3].next.as_mut().unwrap().value = 10;
+            assert_eq!(copy[1].value, 10);
+            copy[3].next.set_null();
+            assert_eq!(sum(&copy[0]), 3);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use core::ops::{Deref, DerefMut};
+
+use crate::Bitena;
+
+/// A saved position in a Bitena, returned by `Bitena::checkpoint`.
+///
+/// Passing it to `Bitena::rewind` releases everything allocated after the
+/// checkpoint was taken, while keeping everything allocated before it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub(crate) arena: usize, // Address of the head chunk's buffer, identifying the arena
+    pub(crate) resets: u64,  // The arena's reset count, since chunk addresses get reused
+    pub(crate) chunk: usize, // Address of the current chunk, 0 for the head chunk
+    pub(crate) end_byte_idx: usize,
+}
+
+/// An RAII guard returned by `Bitena::scope` that rewinds the arena when dropped.
+///
+/// The guard dereferences to the arena, so allocations are made through it as
+/// usual. Those allocations borrow the guard, so the borrow checker rejects any
+/// use of them after the guard, and so the memory they point to, is gone.
+///
+/// # Example
+///
+#[cfg_attr(feature = "alloc", doc = "```rust")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// use bitena::*;
+///
+/// fn main() -> Result<()> {
+///     let mut bitena = Bitena::new(1024)?;
+///     {
+///         let scope = bitena.scope();
+///         let tmp = scope.try_alloc_slice(0u8, 100)?;
+///         assert_eq!(tmp.len(), 100);
+///     }
+///     assert_eq!(bitena.remaining(), 1024);
+///     Ok(())
+/// }
+/// ```
+///
+/// References from inside the scope can't escape it:
+///
+/// ```compile_fail
+/// use bitena::*;
+///
+/// let mut bitena = Bitena::new(1024).unwrap();
+/// let escaped;
+/// {
+///     let scope = bitena.scope();
+///     escaped = scope.alloc(42u32);
+/// }
+/// *escaped += 1;
+/// ```
+pub struct BitenaScope<'s, 'a> {
+    bitena: &'s mut Bitena<'a>,
+    checkpoint: Checkpoint,
+}
+
+impl<'s, 'a> BitenaScope<'s, 'a> {
+    pub(crate) fn new(bitena: &'s mut Bitena<'a>) -> Self {
+        let checkpoint = bitena.checkpoint();
+        Self { bitena, checkpoint }
+    }
+
+    /// Returns the checkpoint this scope rewinds to.
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.checkpoint
+    }
+}
+
+impl<'a> Deref for BitenaScope<'_, 'a> {
+    type Target = Bitena<'a>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.bitena
+    }
+}
+
+impl DerefMut for BitenaScope<'_, '_> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.bitena
+    }
+}
+
+impl Drop for BitenaScope<'_, '_> {
+    fn drop(&mut self) {
+        // Only fails if the arena was reset inside the scope, leaving nothing to rewind
+        let _ = self.bitena.rewind(self.checkpoint);
+    }
+}
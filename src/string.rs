@@ -0,0 +1,215 @@
+use core::fmt;
+use core::ops::Deref;
+
+use crate::{ArenaVec, Bitena, Result};
+
+/// ArenaString
+///
+/// A growable UTF-8 string whose bytes live in a Bitena. It implements
+/// `fmt::Write`, so it can be formatted into directly, without going through
+/// a heap `String` first. See also the `alloc_fmt!` macro.
+///
+/// # Example
+///
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// use bitena::*;
+/// use std::fmt::Write;
+///
+/// fn main() -> Result<()> {
+///     let bitena = Bitena::new(1024)?;
+///     let mut s = ArenaString::new_in(&bitena);
+///     s.push_str("Num:");
+///     s.push(' ');
+///     write!(s, "{}", 42).unwrap();
+///     let s: &str = s.into_str();
+///     assert_eq!(s, "Num: 42");
+///     Ok(())
+/// }
+/// ```
+pub struct ArenaString<'a> {
+    vec: ArenaVec<'a, u8>,
+}
+
+impl<'a> ArenaString<'a> {
+    /// Creates an empty string. Nothing is allocated until text is added.
+    #[inline]
+    pub fn new_in(bitena: &'a Bitena<'a>) -> Self {
+        Self {
+            vec: ArenaVec::new_in(bitena),
+        }
+    }
+
+    /// Creates an empty string with room for at least `capacity` bytes.
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, bitena: &'a Bitena<'a>) -> Self {
+        Self {
+            vec: ArenaVec::with_capacity_in(capacity, bitena),
+        }
+    }
+
+    pub fn try_with_capacity_in(capacity: usize, bitena: &'a Bitena<'a>) -> Result<Self> {
+        Ok(Self {
+            vec: ArenaVec::try_with_capacity_in(capacity, bitena)?,
+        })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // Only ever filled from valid strs and chars
+        unsafe { core::str::from_utf8_unchecked(self.vec.as_slice()) }
+    }
+
+    /// Appends a str to the end of the string.
+    #[inline]
+    pub fn push_str(&mut self, st: &str) {
+        self.vec.extend_from_slice(st.as_bytes());
+    }
+
+    #[inline]
+    pub fn try_push_str(&mut self, st: &str) -> Result<()> {
+        self.vec.try_extend_from_slice(st.as_bytes())
+    }
+
+    /// Appends a char to the end of the string.
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    #[inline]
+    pub fn try_push(&mut self, ch: char) -> Result<()> {
+        self.try_push_str(ch.encode_utf8(&mut [0; 4]))
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+
+    /// Converts the string into a &str that lives as long as the arena.
+    #[inline]
+    pub fn into_str(self) -> &'a str {
+        unsafe { core::str::from_utf8_unchecked(self.vec.into_bump_slice()) }
+    }
+}
+
+impl Deref for ArenaString<'_> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Write for ArenaString<'_> {
+    #[inline]
+    fn write_str(&mut self, st: &str) -> fmt::Result {
+        self.try_push_str(st).map_err(|_| fmt::Error)
+    }
+
+    #[inline]
+    fn write_char(&mut self, ch: char) -> fmt::Result {
+        self.try_push(ch).map_err(|_| fmt::Error)
+    }
+}
+
+impl fmt::Display for ArenaString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Debug for ArenaString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// Formats straight into arena memory, returning a `&str` that lives as long as
+/// the arena. Like `format!`, but without a heap `String`.
+///
+/// Panics, like `alloc`, if the arena runs out of memory.
+///
+/// # Example
+///
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// use bitena::*;
+///
+/// fn main() -> Result<()> {
+///     let bitena = Bitena::new(1024)?;
+///     let num = bitena.try_alloc(42u32)?;
+///     let s = alloc_fmt!(bitena, "Num: {}", *num);
+///     assert_eq!(s, "Num: 42");
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! alloc_fmt {
+    ($bitena:expr, $($arg:tt)*) => {{
+        use ::core::fmt::Write as _;
+        let mut s = $crate::ArenaString::new_in(&$bitena);
+        s.write_fmt(::core::format_args!($($arg)*))
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e));
+        s.into_str()
+    }};
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn test_try_arena_string() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+        let mut s = ArenaString::new_in(&bitena);
+        assert!(s.is_empty());
+
+        s.try_push_str("Grüße")?;
+        s.try_push(' ')?;
+        s.try_push('🦀')?;
+        let name = "x";
+        write!(s, " {}-{:03}", name, 7).unwrap();
+        assert_eq!(s.as_str(), "Grüße 🦀 x-007");
+        assert_eq!(s.len(), "Grüße 🦀 x-007".len());
+        assert!(s.starts_with("Grü"));
+
+        let st: &str = s.into_str();
+        let other = bitena.try_alloc_str("after")?;
+        assert_eq!(st, "Grüße 🦀 x-007");
+        assert_eq!(other, "after");
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_alloc_fmt() -> Result<()> {
+        let bitena = Bitena::new(64)?;
+        let by_ref = &bitena;
+        let a = alloc_fmt!(bitena, "{}+{}", 1, 2);
+        let b = alloc_fmt!(by_ref, "{:?}", [a, "b"]);
+        assert_eq!(a, "1+2");
+        assert_eq!(b, r#"["1+2", "b"]"#);
+
+        let mut s = ArenaString::new_in(&bitena);
+        assert!(write!(s, "{:100}", "too long").is_err());
+        Ok(())
+    }
+}
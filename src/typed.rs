@@ -0,0 +1,256 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::mem::{self, ManuallyDrop};
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::chunk::Chunk;
+use crate::{Bitena, Error, Growth, Result};
+
+/// TypedBitena
+///
+/// An arena for a single type T that, unlike a Bitena, knows what it holds.
+/// It hands out `&mut T` just like `Bitena::try_alloc`, and can also walk,
+/// count, or move out every item in allocation order, so AST or graph nodes
+/// can be visited or dumped without keeping a side index.
+///
+/// Since it knows its items, a TypedBitena drops them when it is reset or
+/// dropped, so T may own heap memory.
+///
+/// # Example
+///
+/// ```
+/// use bitena::*;
+///
+/// fn main() -> Result<()> {
+///     let mut nodes = TypedBitena::new(16)?;
+///     let root = nodes.try_alloc(String::from("root"))?;
+///     root.push_str("!");
+///     nodes.try_alloc(String::from("leaf"))?;
+///     let names: Vec<&String> = nodes.iter().collect();
+///     assert_eq!(names, ["root!", "leaf"]);
+///     assert_eq!(nodes.into_vec(), ["root!", "leaf"]);
+///     Ok(())
+/// }
+/// ```
+pub struct TypedBitena<'a, T> {
+    bitena: Bitena<'a>,
+    len: AtomicUsize,
+    _marker: PhantomData<*mut T>, // Owns Ts, Send and Sync are implemented below
+}
+
+impl<'a, T> TypedBitena<'a, T> {
+    /// Creates a new TypedBitena with room for `capacity` items.
+    /// Returns `Error::ZeroCapacity` if it is zero.
+    pub fn new(capacity: usize) -> Result<Self> {
+        Self::with_growth(capacity, Growth::Fixed, None)
+    }
+
+    /// Creates a new growable TypedBitena. Unlike `Bitena::with_growth`, the
+    /// capacity, any `Growth::Step`, and the max capacity are counted in items.
+    pub fn with_growth(capacity: usize, growth: Growth, max_capacity: Option<usize>) -> Result<Self> {
+        if capacity == 0 {
+            return Err(Error::ZeroCapacity);
+        }
+        let size = mem::size_of::<T>().max(1);
+        let bytes = |items: usize| items.saturating_mul(size);
+        let growth = match growth {
+            Growth::Step(items) => Growth::Step(bytes(items)),
+            growth => growth,
+        };
+        // Leave room to align the first item, wherever the buffer lands
+        let padded = |items: usize| bytes(items).saturating_add(mem::align_of::<T>() - 1);
+        Ok(Self {
+            bitena: Bitena::with_growth(padded(capacity), growth, max_capacity.map(padded))?,
+            len: AtomicUsize::new(0),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Allocates an item and returns a mutable reference to it.
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, val: T) -> &mut T {
+        self.try_alloc(val)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc(&self, val: T) -> Result<&mut T> {
+        let val = self.bitena.try_alloc_leaking(val)?;
+        self.len.fetch_add(1, Ordering::Relaxed);
+        Ok(val)
+    }
+
+    /// Returns the number of items allocated.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over every item, in allocation order.
+    ///
+    /// This takes `&mut self`, like `iter_mut`, since references returned by
+    /// `alloc` may still be alive, and reading through them here would alias.
+    pub fn iter(&mut self) -> impl DoubleEndedIterator<Item = &T> {
+        self.chunk_items()
+            .into_iter()
+            .flat_map(|items| unsafe { &*items }.iter().rev())
+    }
+
+    /// Iterates mutably over every item, in allocation order.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> {
+        self.chunk_items()
+            .into_iter()
+            .flat_map(|items| unsafe { &mut *items }.iter_mut().rev())
+    }
+
+    /// Moves every item out of the arena into a Vec, in allocation order.
+    pub fn into_vec(self) -> Vec<T> {
+        let mut this = ManuallyDrop::new(self);
+        let mut vec = Vec::with_capacity(this.len());
+        vec.extend(this.iter_mut().map(|item| unsafe { ptr::read(item) }));
+        // The items now belong to the Vec, only the arena itself is left to drop
+        unsafe { ptr::drop_in_place(&mut this.bitena) };
+        vec
+    }
+
+    /// Drops every item, then resets the arena.
+    pub fn reset(&mut self) {
+        self.drop_items();
+        self.bitena.reset();
+    }
+
+    /// Returns each chunk's items, oldest chunk first. Within a chunk, items are
+    /// bumped downwards, so they are in reverse allocation order.
+    fn chunk_items(&mut self) -> Vec<*mut [T]> {
+        let size = mem::size_of::<T>();
+        if size == 0 {
+            let len = *self.len.get_mut();
+            return alloc::vec![ptr::slice_from_raw_parts_mut(NonNull::dangling().as_ptr(), len)];
+        }
+
+        let mut items = Vec::new();
+        let mut current = *self.bitena.current.get_mut();
+        loop {
+            let chunk = self.bitena.chunk(current);
+            items.push(Self::items_in(chunk, size));
+            if current.is_null() {
+                break;
+            }
+            current = chunk.prev;
+        }
+        items.reverse();
+        items
+    }
+
+    /// Returns the items in a chunk. They run from the chunk's end_byte_idx up to
+    /// the top of the first item, which is the end of the chunk aligned for T.
+    fn items_in(chunk: &Chunk, size: usize) -> *mut [T] {
+        let base = chunk.buf.as_ptr() as usize;
+        let top = ((base + chunk.layout.size()) & !(mem::align_of::<T>() - 1)).saturating_sub(base);
+        let end_byte_idx = chunk.end_byte_idx.load(Ordering::Relaxed);
+        let count = top.saturating_sub(end_byte_idx) / size;
+        let first = unsafe { chunk.buf.add(end_byte_idx) }.cast::<T>();
+        ptr::slice_from_raw_parts_mut(first.as_ptr(), count)
+    }
+
+    fn drop_items(&mut self) {
+        if mem::needs_drop::<T>() {
+            for item in self.iter_mut() {
+                unsafe { ptr::drop_in_place(item) };
+            }
+        }
+        *self.len.get_mut() = 0;
+    }
+}
+
+impl<T> Drop for TypedBitena<'_, T> {
+    fn drop(&mut self) {
+        self.drop_items();
+    }
+}
+
+// Items may be allocated from one thread and dropped on another
+unsafe impl<T: Send> Send for TypedBitena<'_, T> {}
+unsafe impl<T: Send + Sync> Sync for TypedBitena<'_, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Debug, PartialEq)]
+    struct Node {
+        id: u32,
+        weight: u64,
+    }
+
+    #[test]
+    fn test_try_iter_in_order() -> Result<()> {
+        let mut nodes = TypedBitena::with_growth(4, Growth::Double, None)?;
+        for id in 0..50 {
+            nodes.try_alloc(Node { id, weight: 0 })?;
+        }
+        assert_eq!(nodes.len(), 50);
+        assert!(nodes.bitena.capacity() > 50 * 16, "Should have grown");
+
+        for node in nodes.iter_mut() {
+            node.weight = node.id as u64 * 10;
+        }
+        let ids: Vec<u32> = nodes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, (0..50).collect::<Vec<u32>>());
+        assert_eq!(nodes.iter().next_back().unwrap().weight, 490);
+
+        nodes.reset();
+        assert!(nodes.is_empty());
+        assert_eq!(nodes.iter().count(), 0);
+        nodes.try_alloc(Node { id: 7, weight: 7 })?;
+        assert_eq!(nodes.into_vec(), [Node { id: 7, weight: 7 }]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_drops_items() -> Result<()> {
+        let counter = Arc::new(());
+        let nodes = TypedBitena::new(8)?;
+        for _ in 0..8 {
+            nodes.try_alloc(counter.clone())?;
+        }
+        assert!(nodes.try_alloc(counter.clone()).is_err());
+        assert_eq!(Arc::strong_count(&counter), 9);
+
+        let vec = nodes.into_vec();
+        assert_eq!(Arc::strong_count(&counter), 9, "into_vec moves, not drops");
+        drop(vec);
+        assert_eq!(Arc::strong_count(&counter), 1);
+
+        let nodes = TypedBitena::new(8)?;
+        nodes.try_alloc(counter.clone())?;
+        drop(nodes);
+        assert_eq!(Arc::strong_count(&counter), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_zero_capacity() {
+        assert!(matches!(TypedBitena::<u64>::new(0), Err(Error::ZeroCapacity)));
+        assert!(matches!(TypedBitena::<u8>::with_growth(0, Growth::Double, None), Err(Error::ZeroCapacity)));
+    }
+
+    #[test]
+    fn test_try_zero_sized() -> Result<()> {
+        let mut markers = TypedBitena::new(1)?;
+        for _ in 0..10 {
+            markers.try_alloc(())?;
+        }
+        assert_eq!(markers.iter().count(), 10);
+        assert_eq!(markers.into_vec().len(), 10);
+        Ok(())
+    }
+}
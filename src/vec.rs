@@ -0,0 +1,348 @@
+use core::alloc::Layout;
+use core::fmt;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+
+use crate::{Bitena, Error, Result, assert_no_drop};
+
+/// ArenaVec
+///
+/// A growable vector whose elements live in a Bitena. Growing happens in place
+/// when the vector is the arena's most recent allocation, otherwise the elements
+/// are relocated to a bigger block within the arena, and the old block is left unused.
+///
+/// Like Bitena, it refuses element types that need dropping. Once built, the
+/// vector can be turned into a plain arena slice with `into_slice` or `into_bump_slice`.
+///
+/// # Example
+///
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// use bitena::*;
+///
+/// fn main() -> Result<()> {
+///     let bitena = Bitena::new(1024)?;
+///     let mut v = ArenaVec::new_in(&bitena);
+///     v.push(1u32);
+///     v.extend([2, 3, 4]);
+///     v.insert(0, 0);
+///     v.truncate(4);
+///     let slice: &[u32] = v.into_bump_slice();
+///     assert_eq!(slice, [0, 1, 2, 3]);
+///     Ok(())
+/// }
+/// ```
+pub struct ArenaVec<'a, T> {
+    ptr: NonNull<T>,
+    len: usize,
+    cap: usize,
+    bitena: &'a Bitena<'a>,
+}
+
+impl<'a, T> ArenaVec<'a, T> {
+    /// Creates an empty vector. Nothing is allocated until the first element is added.
+    #[inline]
+    pub fn new_in(bitena: &'a Bitena<'a>) -> Self {
+        assert_no_drop::<T>();
+        Self {
+            ptr: NonNull::dangling(),
+            len: 0,
+            // Zero sized elements never need any memory
+            cap: if mem::size_of::<T>() == 0 { usize::MAX } else { 0 },
+            bitena,
+        }
+    }
+
+    /// Creates an empty vector with room for at least `capacity` elements.
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, bitena: &'a Bitena<'a>) -> Self {
+        Self::try_with_capacity_in(capacity, bitena)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    pub fn try_with_capacity_in(capacity: usize, bitena: &'a Bitena<'a>) -> Result<Self> {
+        let mut vec = Self::new_in(bitena);
+        vec.try_reserve(capacity)?;
+        Ok(vec)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Makes room for at least `additional` more elements.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    pub fn try_reserve(&mut self, additional: usize) -> Result<()> {
+        let needed = self.len.checked_add(additional).ok_or(Error::OutOfMemory)?;
+        if needed <= self.cap {
+            return Ok(());
+        }
+        let new_cap = needed.max(self.cap.saturating_mul(2)).max(4);
+        let old = Layout::array::<T>(self.cap)?;
+        let new = Layout::array::<T>(new_cap)?;
+        self.ptr = unsafe { self.bitena.try_realloc(self.ptr.cast(), old, new)? }.cast();
+        self.cap = new_cap;
+        Ok(())
+    }
+
+    /// Appends an element to the back of the vector.
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        self.try_push(value)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    #[inline]
+    pub fn try_push(&mut self, value: T) -> Result<()> {
+        if self.len == self.cap {
+            self.try_reserve(1)?;
+        }
+        unsafe { self.ptr.add(self.len).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the last element and returns it, or None if the vector is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.ptr.add(self.len).read() })
+    }
+
+    /// Inserts an element at `index`, shifting everything after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    #[inline]
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.try_insert(index, value)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    pub fn try_insert(&mut self, index: usize, value: T) -> Result<()> {
+        assert!(index <= self.len, "Insert index {} is out of bounds, len is {}", index, self.len);
+        if self.len == self.cap {
+            self.try_reserve(1)?;
+        }
+        unsafe {
+            let slot = self.ptr.add(index);
+            ptr::copy(slot.as_ptr(), slot.as_ptr().add(1), self.len - index);
+            slot.write(value);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Shortens the vector to `len` elements. Does nothing if it is already shorter.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Appends copies of every element of `other`.
+    #[inline]
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Copy,
+    {
+        self.try_extend_from_slice(other)
+            .unwrap_or_else(|e| panic!("Bitena Failed: {}", e))
+    }
+
+    pub fn try_extend_from_slice(&mut self, other: &[T]) -> Result<()>
+    where
+        T: Copy,
+    {
+        self.try_reserve(other.len())?;
+        unsafe {
+            ptr::copy_nonoverlapping(other.as_ptr(), self.ptr.add(self.len).as_ptr(), other.len());
+        }
+        self.len += other.len();
+        Ok(())
+    }
+
+    /// Converts the vector into a mutable slice that lives as long as the arena.
+    #[inline]
+    pub fn into_slice(self) -> &'a mut [T] {
+        let slice = unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) };
+        mem::forget(self);
+        slice
+    }
+
+    /// Converts the vector into a read-only slice that lives as long as the arena.
+    #[inline]
+    pub fn into_bump_slice(self) -> &'a [T] {
+        self.into_slice()
+    }
+}
+
+impl<T> Drop for ArenaVec<'_, T> {
+    fn drop(&mut self) {
+        // Give the memory back, if nothing has been allocated after it
+        if mem::size_of::<T>() != 0
+            && self.cap > 0
+            && let Ok(layout) = Layout::array::<T>(self.cap)
+        {
+            self.bitena.unbump(self.ptr.cast(), layout);
+        }
+    }
+}
+
+impl<T> Deref for ArenaVec<'_, T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> DerefMut for ArenaVec<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T> Extend<T> for ArenaVec<'_, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'v, T> IntoIterator for &'v ArenaVec<'_, T> {
+    type Item = &'v T;
+    type IntoIter = core::slice::Iter<'v, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'v, T> IntoIterator for &'v mut ArenaVec<'_, T> {
+    type Item = &'v mut T;
+    type IntoIter = core::slice::IterMut<'v, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArenaVec<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+unsafe impl<T: Send> Send for ArenaVec<'_, T> {}
+unsafe impl<T: Sync> Sync for ArenaVec<'_, T> {}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_grows_in_place() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+        let mut v = ArenaVec::new_in(&bitena);
+        v.try_push(1u32)?;
+        assert_eq!(v.capacity(), 4);
+        let first = v.as_ptr() as usize;
+
+        v.extend(2..=10);
+        assert_eq!(v.capacity(), 10);
+        assert_eq!(v.as_ptr() as usize + 4 * 10, first + 4 * 4, "The block grows downwards in place");
+        assert_eq!(bitena.remaining(), 1024 - 40);
+        assert_eq!(v.as_slice(), (1..=10).collect::<Vec<u32>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_relocates() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+        let mut a = ArenaVec::with_capacity_in(4, &bitena);
+        a.extend_from_slice(&[1u64, 2, 3, 4]);
+        let b = bitena.try_alloc(99u64)?;
+
+        a.try_insert(1, 7)?;
+        assert_eq!(a.capacity(), 8);
+        assert_eq!(a.as_slice(), [1, 7, 2, 3, 4]);
+        assert_eq!(*b, 99, "Relocating must not disturb later allocations");
+
+        assert_eq!(a.pop(), Some(4));
+        a.truncate(1);
+        a.truncate(5);
+        assert_eq!(a.into_slice(), [1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_out_of_memory() -> Result<()> {
+        let bitena = Bitena::new(16)?;
+        let mut v = ArenaVec::new_in(&bitena);
+        for i in 0..4u32 {
+            v.try_push(i)?;
+        }
+        assert!(matches!(v.try_push(4), Err(Error::OutOfMemory)));
+        assert_eq!(v.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_drop_gives_back() -> Result<()> {
+        let bitena = Bitena::new(1024)?;
+        {
+            let mut v = ArenaVec::new_in(&bitena);
+            v.extend(0..100u8);
+        }
+        assert_eq!(bitena.remaining(), 1024);
+
+        let mut zsts = ArenaVec::new_in(&bitena);
+        zsts.extend(std::iter::repeat_n((), 1000));
+        assert_eq!(zsts.len(), 1000);
+        assert_eq!(bitena.remaining(), 1024);
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use core::mem::MaybeUninit;
+use core::num::NonZero;
+
+/// Types for which a value made of all zero bytes is valid.
+///
+/// Used by `alloc_zeroed_slice`, which hands out freshly zeroed memory without
+/// running any initializers.
+///
+/// # Safety
+///
+/// Implementors must guarantee every bit pattern of all zeros is a valid value
+/// of the type, including any padding and nested fields.
+///
+/// # Example
+///
+/// ```
+/// use bitena::Zeroable;
+///
+/// #[repr(C)]
+/// struct Header {
+///     id: u32,
+///     flags: u32,
+/// }
+///
+/// unsafe impl Zeroable for Header {}
+/// ```
+pub unsafe trait Zeroable {}
+
+macro_rules! impl_zeroable {
+    ($($t:ty),*) => {
+        $(unsafe impl Zeroable for $t {})*
+    };
+}
+
+impl_zeroable!(u8, u16, u32, u64, u128, usize);
+impl_zeroable!(i8, i16, i32, i64, i128, isize);
+impl_zeroable!(f32, f64, bool, char, ());
+
+unsafe impl<T: Zeroable, const N: usize> Zeroable for [T; N] {}
+unsafe impl<T> Zeroable for MaybeUninit<T> {}
+unsafe impl<T> Zeroable for *const T {}
+unsafe impl<T> Zeroable for *mut T {}
+
+impl_zeroable!(Option<NonZero<u8>>, Option<NonZero<u16>>, Option<NonZero<u32>>);
+impl_zeroable!(Option<NonZero<u64>>, Option<NonZero<u128>>, Option<NonZero<usize>>);
+impl_zeroable!(Option<NonZero<i8>>, Option<NonZero<i16>>, Option<NonZero<i32>>);
+impl_zeroable!(Option<NonZero<i64>>, Option<NonZero<i128>>, Option<NonZero<isize>>);